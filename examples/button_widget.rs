@@ -16,7 +16,9 @@
 extern crate pushrod;
 extern crate sdl2;
 
+use pushrod::config::EngineConfig;
 use pushrod::engine::{Engine, EventHandler};
+use pushrod::Error;
 use pushrod_widgets::caches::WidgetCache;
 use pushrod_widgets::event::Event::Pushrod;
 use pushrod_widgets::event::{Event, PushrodEvent};
@@ -152,9 +154,13 @@ impl EventHandler for PushrodExample {
     }
 }
 
-pub fn main() {
+pub fn main() -> Result<(), Error> {
     let (sdl_context, _, window) = init_application("pushrod example", 400, 260);
-    let mut engine = Engine::new(Box::new(PushrodExample::default()), &window);
+    let mut engine = Engine::new(
+        Box::new(PushrodExample::default()),
+        &window,
+        EngineConfig::default(),
+    )?;
 
-    engine.run(sdl_context, window);
+    engine.run(sdl_context, window)
 }
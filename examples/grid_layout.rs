@@ -0,0 +1,46 @@
+// Pushrod
+// Grid Layout Example
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate pushrod;
+extern crate sdl2;
+
+use pushrod::config::EngineConfig;
+use pushrod::engine::{Engine, EventHandler};
+use pushrod::layout::load_layout;
+use pushrod::Error;
+use pushrod_widgets::caches::WidgetCache;
+use pushrod_widgets::primitives::init_application;
+
+#[derive(Default)]
+pub struct PushrodExample {}
+
+impl EventHandler for PushrodExample {
+    fn build_layout(&mut self, cache: &mut WidgetCache) {
+        if let Err(e) = load_layout("assets/examples/grid_widget.rsc", cache) {
+            eprintln!("Unable to load layout: {}", e);
+        }
+    }
+}
+
+pub fn main() -> Result<(), Error> {
+    let (sdl_context, _, window) = init_application("pushrod example", 620, 480);
+    let mut engine = Engine::new(
+        Box::new(PushrodExample::default()),
+        &window,
+        EngineConfig::default(),
+    )?;
+
+    engine.run(sdl_context, window)
+}
@@ -16,7 +16,9 @@
 extern crate pushrod;
 extern crate sdl2;
 
+use pushrod::config::EngineConfig;
 use pushrod::engine::{Engine, EventHandler};
+use pushrod::Error;
 use pushrod_widgets::caches::WidgetCache;
 use pushrod_widgets::event::Event::Pushrod;
 use pushrod_widgets::event::{Event, PushrodEvent};
@@ -30,19 +32,135 @@ use pushrod_widgets::system_widgets::base_widget::BaseWidget;
 use pushrod_widgets::system_widgets::button_widget::ButtonWidget;
 use pushrod_widgets::system_widgets::group_box_widget::GroupBoxWidget;
 use pushrod_widgets::system_widgets::text_widget::TextWidget;
-use pushrod_widgets::widget::Widget;
 use sdl2::pixels::Color;
+use sdl2::ttf::FontStyle;
+
+pushrod::layout! {
+    #[derive(Default)]
+    pub struct HideShowIds {
+        group_box: GroupBoxWidget in root {
+            origin(10, 6),
+            bounds(650, 194),
+            value(PROPERTY_BORDER_WIDTH, 1),
+            color(PROPERTY_BORDER_COLOR, Color::BLACK),
+            color(PROPERTY_GROUP_BACKGROUND_COLOR, Color::RGBA(224, 224, 244, 255)),
+            text(PROPERTY_FONT_NAME, "assets/OpenSans-Regular.ttf"),
+            value(PROPERTY_FONT_SIZE, 14),
+            value(PROPERTY_FONT_STYLE, FontStyle::NORMAL.bits()),
+            text(PROPERTY_TEXT, " HIDE / SHOW EXAMPLE "),
+        },
+        base1: BaseWidget in root {
+            origin(20, 36),
+            bounds(150, 150),
+            color(PROPERTY_MAIN_COLOR, Color::RED),
+            value(PROPERTY_BORDER_WIDTH, 2),
+            color(PROPERTY_BORDER_COLOR, Color::BLACK),
+        },
+        base2: BaseWidget in root {
+            origin(180, 36),
+            bounds(150, 150),
+            color(PROPERTY_MAIN_COLOR, Color::GREEN),
+            value(PROPERTY_BORDER_WIDTH, 2),
+            color(PROPERTY_BORDER_COLOR, Color::BLACK),
+        },
+        base2_1: BaseWidget in base2 {
+            origin(210, 56),
+            bounds(110, 110),
+            color(PROPERTY_MAIN_COLOR, Color::GREY),
+            value(PROPERTY_BORDER_WIDTH, 2),
+            color(PROPERTY_BORDER_COLOR, Color::RED),
+        },
+        text1: TextWidget in root {
+            origin(360, 86),
+            bounds(130, 26),
+            text(PROPERTY_FONT_NAME, "assets/OpenSans-Regular.ttf"),
+            value(PROPERTY_FONT_SIZE, 18),
+            value(PROPERTY_FONT_STYLE, FontStyle::NORMAL.bits()),
+            color(PROPERTY_MAIN_COLOR, Color::RGBA(255, 255, 255, 1)),
+            text(PROPERTY_TEXT, "BOO!!!"),
+        },
+        base3: BaseWidget in root {
+            origin(340, 36),
+            bounds(150, 150),
+            color(PROPERTY_MAIN_COLOR, Color::MAGENTA),
+            value(PROPERTY_BORDER_WIDTH, 2),
+            color(PROPERTY_BORDER_COLOR, Color::BLACK),
+        },
+        base4: BaseWidget in root {
+            origin(500, 36),
+            bounds(150, 150),
+            color(PROPERTY_MAIN_COLOR, Color::CYAN),
+            value(PROPERTY_BORDER_WIDTH, 2),
+            color(PROPERTY_BORDER_COLOR, Color::BLACK),
+        },
+        base4_1: BaseWidget in base4 {
+            origin(520, 56),
+            bounds(110, 110),
+            color(PROPERTY_MAIN_COLOR, Color::GREEN),
+            value(PROPERTY_BORDER_WIDTH, 2),
+            color(PROPERTY_BORDER_COLOR, Color::BLACK),
+        },
+        base4_2: BaseWidget in base4_1 {
+            origin(560, 76),
+            bounds(100, 70),
+            color(PROPERTY_MAIN_COLOR, Color::WHITE),
+            value(PROPERTY_BORDER_WIDTH, 2),
+            color(PROPERTY_BORDER_COLOR, Color::BLACK),
+        },
+        button1: ButtonWidget in root {
+            origin(20, 210),
+            bounds(150, 32),
+            color(PROPERTY_MAIN_COLOR, Color::WHITE),
+            text(PROPERTY_FONT_NAME, "assets/OpenSans-Regular.ttf"),
+            value(PROPERTY_FONT_SIZE, 18),
+            value(PROPERTY_FONT_STYLE, FontStyle::NORMAL.bits()),
+            value(PROPERTY_TEXT_JUSTIFICATION, TEXT_JUSTIFY_CENTER),
+            value(PROPERTY_BORDER_WIDTH, 2),
+            color(PROPERTY_BORDER_COLOR, Color::BLACK),
+            text(PROPERTY_TEXT, "Hide/Show"),
+        },
+        button2: ButtonWidget in root {
+            origin(180, 210),
+            bounds(150, 32),
+            color(PROPERTY_MAIN_COLOR, Color::WHITE),
+            text(PROPERTY_FONT_NAME, "assets/OpenSans-Regular.ttf"),
+            value(PROPERTY_FONT_SIZE, 18),
+            value(PROPERTY_FONT_STYLE, FontStyle::NORMAL.bits()),
+            value(PROPERTY_TEXT_JUSTIFICATION, TEXT_JUSTIFY_CENTER),
+            value(PROPERTY_BORDER_WIDTH, 2),
+            color(PROPERTY_BORDER_COLOR, Color::BLACK),
+            text(PROPERTY_TEXT, "Hide/Show"),
+        },
+        button3: ButtonWidget in root {
+            origin(340, 210),
+            bounds(150, 32),
+            color(PROPERTY_MAIN_COLOR, Color::WHITE),
+            text(PROPERTY_FONT_NAME, "assets/OpenSans-Regular.ttf"),
+            value(PROPERTY_FONT_SIZE, 18),
+            value(PROPERTY_FONT_STYLE, FontStyle::NORMAL.bits()),
+            value(PROPERTY_TEXT_JUSTIFICATION, TEXT_JUSTIFY_CENTER),
+            value(PROPERTY_BORDER_WIDTH, 2),
+            color(PROPERTY_BORDER_COLOR, Color::BLACK),
+            text(PROPERTY_TEXT, "Hide/Show"),
+        },
+        button4: ButtonWidget in root {
+            origin(500, 210),
+            bounds(150, 32),
+            color(PROPERTY_MAIN_COLOR, Color::WHITE),
+            text(PROPERTY_FONT_NAME, "assets/OpenSans-Regular.ttf"),
+            value(PROPERTY_FONT_SIZE, 18),
+            value(PROPERTY_FONT_STYLE, FontStyle::NORMAL.bits()),
+            value(PROPERTY_TEXT_JUSTIFICATION, TEXT_JUSTIFY_CENTER),
+            value(PROPERTY_BORDER_WIDTH, 2),
+            color(PROPERTY_BORDER_COLOR, Color::BLACK),
+            text(PROPERTY_TEXT, "Hide/Show"),
+        },
+    }
+}
 
 #[derive(Default)]
 pub struct PushrodExample {
-    base1_id: u32,
-    base2_id: u32,
-    base3_id: u32,
-    base4_id: u32,
-    button1_id: u32,
-    button2_id: u32,
-    button3_id: u32,
-    button4_id: u32,
+    ids: HideShowIds,
 }
 
 impl PushrodExample {
@@ -70,14 +188,14 @@ impl EventHandler for PushrodExample {
                     clicks,
                 } => {
                     if button == 1 && clicks == 1 {
-                        if widget_id == self.button1_id {
-                            self.toggle_hide_show(self.base1_id, cache);
-                        } else if widget_id == self.button2_id {
-                            self.toggle_hide_show(self.base2_id, cache);
-                        } else if widget_id == self.button3_id {
-                            self.toggle_hide_show(self.base3_id, cache);
-                        } else if widget_id == self.button4_id {
-                            self.toggle_hide_show(self.base4_id, cache);
+                        if widget_id == self.ids.button1 {
+                            self.toggle_hide_show(self.ids.base1, cache);
+                        } else if widget_id == self.ids.button2 {
+                            self.toggle_hide_show(self.ids.base2, cache);
+                        } else if widget_id == self.ids.button3 {
+                            self.toggle_hide_show(self.ids.base3, cache);
+                        } else if widget_id == self.ids.button4 {
+                            self.toggle_hide_show(self.ids.base4_1, cache);
                         }
                     }
                 }
@@ -90,214 +208,17 @@ impl EventHandler for PushrodExample {
     }
 
     fn build_layout(&mut self, cache: &mut WidgetCache) {
-        let mut group_box = GroupBoxWidget::default();
-
-        group_box
-            .properties()
-            .set_origin(10, 6)
-            .set_bounds(650, 194)
-            .set_value(PROPERTY_BORDER_WIDTH, 1)
-            .set_color(PROPERTY_BORDER_COLOR, Color::BLACK)
-            .set_color(
-                PROPERTY_GROUP_BACKGROUND_COLOR,
-                Color::RGBA(224, 224, 244, 255),
-            )
-            .set(
-                PROPERTY_FONT_NAME,
-                String::from("assets/OpenSans-Regular.ttf"),
-            )
-            .set_value(PROPERTY_FONT_SIZE, 14)
-            .set_value(PROPERTY_FONT_STYLE, sdl2::ttf::FontStyle::NORMAL.bits())
-            .set(PROPERTY_TEXT, String::from(" HIDE / SHOW EXAMPLE "));
-
-        cache.add(Box::new(group_box), String::from("group_box"), 0);
-
-        let mut base1 = BaseWidget::default();
-
-        base1
-            .properties()
-            .set_origin(20, 36)
-            .set_bounds(150, 150)
-            .set_color(PROPERTY_MAIN_COLOR, Color::RED)
-            .set_value(PROPERTY_BORDER_WIDTH, 2)
-            .set_color(PROPERTY_BORDER_COLOR, Color::BLACK);
-
-        self.base1_id = cache.add(Box::new(base1), String::from("base1"), 0);
-
-        let mut base2 = BaseWidget::default();
-
-        base2
-            .properties()
-            .set_origin(180, 36)
-            .set_bounds(150, 150)
-            .set_color(PROPERTY_MAIN_COLOR, Color::GREEN)
-            .set_value(PROPERTY_BORDER_WIDTH, 2)
-            .set_color(PROPERTY_BORDER_COLOR, Color::BLACK);
-
-        self.base2_id = cache.add(Box::new(base2), String::from("base2"), 0);
-
-        let mut base2_1 = BaseWidget::default();
-
-        base2_1
-            .properties()
-            .set_origin(210, 56)
-            .set_bounds(110, 110)
-            .set_color(PROPERTY_MAIN_COLOR, Color::GREY)
-            .set_value(PROPERTY_BORDER_WIDTH, 2)
-            .set_color(PROPERTY_BORDER_COLOR, Color::RED);
-
-        cache.add(Box::new(base2_1), String::from("base2_1"), self.base2_id);
-
-        let mut text1 = TextWidget::default();
-
-        text1
-            .properties()
-            .set_origin(360, 86)
-            .set_bounds(130, 26)
-            .set(
-                PROPERTY_FONT_NAME,
-                String::from("assets/OpenSans-Regular.ttf"),
-            )
-            .set_value(PROPERTY_FONT_SIZE, 18)
-            .set_value(PROPERTY_FONT_STYLE, sdl2::ttf::FontStyle::NORMAL.bits())
-            .set_color(PROPERTY_MAIN_COLOR, Color::RGBA(255, 255, 255, 1))
-            .set(PROPERTY_TEXT, String::from("BOO!!!"));
-
-        cache.add(Box::new(text1), String::from("text1"), 0);
-
-        let mut base3 = BaseWidget::default();
-
-        base3
-            .properties()
-            .set_origin(340, 36)
-            .set_bounds(150, 150)
-            .set_color(PROPERTY_MAIN_COLOR, Color::MAGENTA)
-            .set_value(PROPERTY_BORDER_WIDTH, 2)
-            .set_color(PROPERTY_BORDER_COLOR, Color::BLACK);
-
-        self.base3_id = cache.add(Box::new(base3), String::from("base3"), 0);
-
-        let mut base4 = BaseWidget::default();
-
-        base4
-            .properties()
-            .set_origin(500, 36)
-            .set_bounds(150, 150)
-            .set_color(PROPERTY_MAIN_COLOR, Color::CYAN)
-            .set_value(PROPERTY_BORDER_WIDTH, 2)
-            .set_color(PROPERTY_BORDER_COLOR, Color::BLACK);
-
-        let base4_id = cache.add(Box::new(base4), String::from("base4"), 0);
-
-        let mut base4_1 = BaseWidget::default();
-
-        base4_1
-            .properties()
-            .set_origin(520, 56)
-            .set_bounds(110, 110)
-            .set_color(PROPERTY_MAIN_COLOR, Color::GREEN)
-            .set_value(PROPERTY_BORDER_WIDTH, 2)
-            .set_color(PROPERTY_BORDER_COLOR, Color::BLACK);
-
-        self.base4_id = cache.add(Box::new(base4_1), String::from("base4_1"), base4_id);
-
-        let mut base4_2 = BaseWidget::default();
-
-        base4_2
-            .properties()
-            .set_origin(560, 76)
-            .set_bounds(100, 70)
-            .set_color(PROPERTY_MAIN_COLOR, Color::WHITE)
-            .set_value(PROPERTY_BORDER_WIDTH, 2)
-            .set_color(PROPERTY_BORDER_COLOR, Color::BLACK);
-
-        cache.add(Box::new(base4_2), String::from("base4_2"), self.base4_id);
-
-        let mut button1 = ButtonWidget::default();
-
-        button1
-            .properties()
-            .set_origin(20, 210)
-            .set_bounds(150, 32)
-            .set_color(PROPERTY_MAIN_COLOR, Color::WHITE)
-            .set(
-                PROPERTY_FONT_NAME,
-                String::from("assets/OpenSans-Regular.ttf"),
-            )
-            .set_value(PROPERTY_FONT_SIZE, 18)
-            .set_value(PROPERTY_FONT_STYLE, sdl2::ttf::FontStyle::NORMAL.bits())
-            .set_value(PROPERTY_TEXT_JUSTIFICATION, TEXT_JUSTIFY_CENTER)
-            .set_value(PROPERTY_BORDER_WIDTH, 2)
-            .set_color(PROPERTY_BORDER_COLOR, Color::BLACK)
-            .set(PROPERTY_TEXT, String::from("Hide/Show"));
-
-        self.button1_id = cache.add(Box::new(button1), String::from("button1"), 0);
-
-        let mut button2 = ButtonWidget::default();
-
-        button2
-            .properties()
-            .set_origin(180, 210)
-            .set_bounds(150, 32)
-            .set_color(PROPERTY_MAIN_COLOR, Color::WHITE)
-            .set(
-                PROPERTY_FONT_NAME,
-                String::from("assets/OpenSans-Regular.ttf"),
-            )
-            .set_value(PROPERTY_FONT_SIZE, 18)
-            .set_value(PROPERTY_FONT_STYLE, sdl2::ttf::FontStyle::NORMAL.bits())
-            .set_value(PROPERTY_TEXT_JUSTIFICATION, TEXT_JUSTIFY_CENTER)
-            .set_value(PROPERTY_BORDER_WIDTH, 2)
-            .set_color(PROPERTY_BORDER_COLOR, Color::BLACK)
-            .set(PROPERTY_TEXT, String::from("Hide/Show"));
-
-        self.button2_id = cache.add(Box::new(button2), String::from("button2"), 0);
-
-        let mut button3 = ButtonWidget::default();
-
-        button3
-            .properties()
-            .set_origin(340, 210)
-            .set_bounds(150, 32)
-            .set_color(PROPERTY_MAIN_COLOR, Color::WHITE)
-            .set(
-                PROPERTY_FONT_NAME,
-                String::from("assets/OpenSans-Regular.ttf"),
-            )
-            .set_value(PROPERTY_FONT_SIZE, 18)
-            .set_value(PROPERTY_FONT_STYLE, sdl2::ttf::FontStyle::NORMAL.bits())
-            .set_value(PROPERTY_TEXT_JUSTIFICATION, TEXT_JUSTIFY_CENTER)
-            .set_value(PROPERTY_BORDER_WIDTH, 2)
-            .set_color(PROPERTY_BORDER_COLOR, Color::BLACK)
-            .set(PROPERTY_TEXT, String::from("Hide/Show"));
-
-        self.button3_id = cache.add(Box::new(button3), String::from("button3"), 0);
-
-        let mut button4 = ButtonWidget::default();
-
-        button4
-            .properties()
-            .set_origin(500, 210)
-            .set_bounds(150, 32)
-            .set_color(PROPERTY_MAIN_COLOR, Color::WHITE)
-            .set(
-                PROPERTY_FONT_NAME,
-                String::from("assets/OpenSans-Regular.ttf"),
-            )
-            .set_value(PROPERTY_FONT_SIZE, 18)
-            .set_value(PROPERTY_FONT_STYLE, sdl2::ttf::FontStyle::NORMAL.bits())
-            .set_value(PROPERTY_TEXT_JUSTIFICATION, TEXT_JUSTIFY_CENTER)
-            .set_value(PROPERTY_BORDER_WIDTH, 2)
-            .set_color(PROPERTY_BORDER_COLOR, Color::BLACK)
-            .set(PROPERTY_TEXT, String::from("Hide/Show"));
-
-        self.button4_id = cache.add(Box::new(button4), String::from("button4"), 0);
+        self.ids = HideShowIds::build(cache);
     }
 }
 
-pub fn main() {
+pub fn main() -> Result<(), Error> {
     let (sdl_context, _, window) = init_application("pushrod example", 670, 250);
-    let mut engine = Engine::new(Box::new(PushrodExample::default()), &window);
+    let mut engine = Engine::new(
+        Box::new(PushrodExample::default()),
+        &window,
+        EngineConfig::default(),
+    )?;
 
-    engine.run(sdl_context, window);
+    engine.run(sdl_context, window)
 }
@@ -0,0 +1,130 @@
+// Pushrod
+// Multiple Window Example
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate pushrod;
+extern crate sdl2;
+
+use pushrod::application::{Application, ApplicationHandle};
+use pushrod::config::EngineConfig;
+use pushrod::engine::EventHandler;
+use pushrod::Error;
+use pushrod_widgets::caches::WidgetCache;
+use pushrod_widgets::event::Event::Pushrod;
+use pushrod_widgets::event::{Event, PushrodEvent};
+use pushrod_widgets::primitives::init_application;
+use pushrod_widgets::properties::{
+    PROPERTY_BORDER_COLOR, PROPERTY_BORDER_WIDTH, PROPERTY_FONT_NAME, PROPERTY_FONT_SIZE,
+    PROPERTY_FONT_STYLE, PROPERTY_MAIN_COLOR, PROPERTY_TEXT, PROPERTY_TEXT_JUSTIFICATION,
+    TEXT_JUSTIFY_CENTER,
+};
+use pushrod_widgets::system_widgets::base_widget::BaseWidget;
+use pushrod_widgets::system_widgets::button_widget::ButtonWidget;
+use pushrod_widgets::widget::Widget;
+use sdl2::pixels::Color;
+
+/// This is the main window, which opens a new palette window each time its button is clicked.
+/// Palettes alternate between red and blue.
+pub struct MainWindow {
+    handle: ApplicationHandle,
+    button_id: u32,
+    red_palette: bool,
+}
+
+/// This is a palette window, which contains a single colored box.
+pub struct PaletteWindow {
+    color: Color,
+}
+
+impl EventHandler for MainWindow {
+    fn handle_event(&mut self, event: Event, _cache: &mut WidgetCache) {
+        if let Pushrod(PushrodEvent::WidgetClicked {
+            widget_id, button, ..
+        }) = event
+        {
+            if widget_id == self.button_id && button == 1 {
+                let color = if self.red_palette {
+                    Color::RED
+                } else {
+                    Color::BLUE
+                };
+
+                self.red_palette = !self.red_palette;
+                self.handle.open_window(move |video| {
+                    let window = video
+                        .window("pushrod palette", 200, 200)
+                        .position_centered()
+                        .build()
+                        .map_err(|e| Error::Window(e.to_string()))?;
+
+                    Ok((
+                        Box::new(PaletteWindow { color }) as Box<dyn EventHandler>,
+                        window,
+                        EngineConfig::default(),
+                    ))
+                });
+            }
+        }
+    }
+
+    fn build_layout(&mut self, cache: &mut WidgetCache) {
+        let mut button1 = ButtonWidget::default();
+
+        button1
+            .properties()
+            .set_origin(20, 20)
+            .set_bounds(360, 60)
+            .set_color(PROPERTY_MAIN_COLOR, Color::WHITE)
+            .set(
+                PROPERTY_FONT_NAME,
+                String::from("assets/OpenSans-Regular.ttf"),
+            )
+            .set_value(PROPERTY_FONT_SIZE, 20)
+            .set_value(PROPERTY_FONT_STYLE, sdl2::ttf::FontStyle::NORMAL.bits())
+            .set_value(PROPERTY_TEXT_JUSTIFICATION, TEXT_JUSTIFY_CENTER)
+            .set_value(PROPERTY_BORDER_WIDTH, 2)
+            .set_color(PROPERTY_BORDER_COLOR, Color::BLACK)
+            .set(PROPERTY_TEXT, String::from("Open Palette"));
+
+        self.button_id = cache.add(Box::new(button1), String::from("button1"), 0);
+    }
+}
+
+impl EventHandler for PaletteWindow {
+    fn build_layout(&mut self, cache: &mut WidgetCache) {
+        let mut box1 = BaseWidget::default();
+
+        box1.properties()
+            .set_origin(20, 20)
+            .set_bounds(160, 160)
+            .set_value(PROPERTY_BORDER_WIDTH, 1)
+            .set_color(PROPERTY_BORDER_COLOR, Color::BLACK)
+            .set_color(PROPERTY_MAIN_COLOR, self.color);
+
+        cache.add(Box::new(box1), String::from("box1"), 0);
+    }
+}
+
+pub fn main() -> Result<(), Error> {
+    let (sdl_context, _ttf_context, window) = init_application("pushrod example", 400, 100);
+    let mut application = Application::new(sdl_context)?;
+    let main_window = MainWindow {
+        handle: application.handle(),
+        button_id: 0,
+        red_palette: true,
+    };
+
+    application.open_window(Box::new(main_window), window, EngineConfig::default())?;
+    application.run()
+}
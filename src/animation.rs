@@ -0,0 +1,462 @@
+// Pushrod Animation
+// Property Animation Service
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use sdl2::pixels::Color;
+
+use pushrod_widgets::caches::WidgetCache;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// This is the easing curve that is applied to the progress of an animation, controlling how it
+/// accelerates and decelerates over its duration.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Easing {
+    /// Progresses at a constant rate.
+    Linear,
+
+    /// Starts slowly, and accelerates toward the end.
+    EaseIn,
+
+    /// Starts quickly, and decelerates toward the end.
+    EaseOut,
+
+    /// Starts slowly, accelerates through the middle, and decelerates toward the end.
+    EaseInOut,
+}
+
+/// This is an implementation of the `Easing` curves, which are quadratic.
+impl Easing {
+    /// Applies the easing curve to `t`, the linear progress of an animation from `0.0` to `1.0`,
+    /// returning the eased progress.
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    -1.0 + (4.0 - 2.0 * t) * t
+                }
+            }
+        }
+    }
+}
+
+/// This is a `Widget` property that can be animated, along with the values between which it is
+/// animated.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AnimatedProperty {
+    /// Animates the origin of the `Widget`.
+    Origin { from: (u32, u32), to: (u32, u32) },
+
+    /// Animates the bounds of the `Widget`.
+    Bounds { from: (u32, u32), to: (u32, u32) },
+
+    /// Animates a color property of the `Widget`, such as `PROPERTY_MAIN_COLOR`.
+    Color {
+        property_key: u32,
+        from: Color,
+        to: Color,
+    },
+
+    /// Animates a numeric property of the `Widget`, such as `PROPERTY_PROGRESS`.
+    Value {
+        property_key: u32,
+        from: i32,
+        to: i32,
+    },
+}
+
+/// Interpolates between two `u32` values.
+fn lerp_u32(from: u32, to: u32, t: f64) -> u32 {
+    (from as f64 + (to as f64 - from as f64) * t).round() as u32
+}
+
+/// Interpolates between two `i32` values.
+fn lerp_i32(from: i32, to: i32, t: f64) -> i32 {
+    (from as f64 + (to as f64 - from as f64) * t).round() as i32
+}
+
+/// Interpolates between two `u8` color components.
+fn lerp_u8(from: u8, to: u8, t: f64) -> u8 {
+    (from as f64 + (to as f64 - from as f64) * t).round() as u8
+}
+
+/// This is an implementation of the `AnimatedProperty`, which applies its interpolated value.
+impl AnimatedProperty {
+    /// Sets the value of this property at the eased progress `t` on the `Widget` with the ID
+    /// `widget_id`, invalidating it.  Changes to origin or bounds also invalidate the top-level
+    /// `Widget`, so that the area previously covered by the `Widget` is redrawn.
+    fn apply(&self, widget_id: u32, t: f64, cache: &mut WidgetCache) {
+        let moved = {
+            let mut widget = cache.get(widget_id);
+
+            match self {
+                AnimatedProperty::Origin { from, to } => {
+                    widget
+                        .properties()
+                        .set_origin(lerp_u32(from.0, to.0, t), lerp_u32(from.1, to.1, t));
+                }
+
+                AnimatedProperty::Bounds { from, to } => {
+                    widget
+                        .properties()
+                        .set_bounds(lerp_u32(from.0, to.0, t), lerp_u32(from.1, to.1, t));
+                }
+
+                AnimatedProperty::Color {
+                    property_key,
+                    from,
+                    to,
+                } => {
+                    widget.properties().set_color(
+                        *property_key,
+                        Color::RGBA(
+                            lerp_u8(from.r, to.r, t),
+                            lerp_u8(from.g, to.g, t),
+                            lerp_u8(from.b, to.b, t),
+                            lerp_u8(from.a, to.a, t),
+                        ),
+                    );
+                }
+
+                AnimatedProperty::Value {
+                    property_key,
+                    from,
+                    to,
+                } => {
+                    widget
+                        .properties()
+                        .set_value(*property_key, lerp_i32(*from, *to, t));
+                }
+            }
+
+            widget.invalidate();
+
+            matches!(
+                self,
+                AnimatedProperty::Origin { .. } | AnimatedProperty::Bounds { .. }
+            )
+        };
+
+        if moved && widget_id != 0 {
+            cache.get(0).invalidate();
+        }
+    }
+}
+
+/// This is a single running animation.  Its start time is set on the first frame after it is
+/// added, so that it always starts from its `from` value.
+struct Animation {
+    animation_id: u32,
+    widget_id: u32,
+    property: AnimatedProperty,
+    duration_ms: u128,
+    easing: Easing,
+    start: Option<u128>,
+}
+
+/// This is the list of running animations.
+#[derive(Default)]
+struct AnimationQueue {
+    next_animation_id: u32,
+    animations: Vec<Animation>,
+}
+
+/// This is the animation service for an `Engine`.  Animations are advanced on every frame, setting
+/// the animated property of the `Widget` and invalidating it.  When an animation completes, an
+/// `AnimationFinished` event is sent to the `EventHandler`.  While any animation is running, the
+/// `Engine` draws frames continuously, even in `LoopMode::EventDriven`.
+///
+/// `Animations` can be cloned, and all clones share the same animations, so a copy can be kept in
+/// the `EventHandler`, and another given to the `Engine` using `Engine::set_animations`:
+///
+/// ```rust,no_run
+/// use pushrod::animation::{AnimatedProperty, Animations, Easing};
+/// use pushrod_widgets::properties::PROPERTY_PROGRESS;
+///
+/// let animations = Animations::default();
+///
+/// animations.animate(
+///     1,
+///     AnimatedProperty::Value {
+///         property_key: PROPERTY_PROGRESS,
+///         from: 0,
+///         to: 100,
+///     },
+///     500,
+///     Easing::EaseInOut,
+/// );
+/// ```
+#[derive(Clone, Default)]
+pub struct Animations {
+    queue: Rc<RefCell<AnimationQueue>>,
+}
+
+/// This is an implementation of the `Animations` service.
+impl Animations {
+    /// Starts animating the `property` of the `Widget` with the ID `widget_id` over `duration_ms`
+    /// milliseconds, using the `easing` curve.  Returns the ID of the animation.
+    pub fn animate(
+        &self,
+        widget_id: u32,
+        property: AnimatedProperty,
+        duration_ms: u128,
+        easing: Easing,
+    ) -> u32 {
+        let mut queue = self.queue.borrow_mut();
+
+        queue.next_animation_id += 1;
+
+        let animation = Animation {
+            animation_id: queue.next_animation_id,
+            widget_id,
+            property,
+            duration_ms,
+            easing,
+            start: None,
+        };
+
+        queue.animations.push(animation);
+        queue.next_animation_id
+    }
+
+    /// Cancels the animation with the ID `animation_id`, leaving the property at its current value.
+    /// Returns `true` if the animation was running, or `false` if it had already finished or been
+    /// cancelled.  No `AnimationFinished` event is sent for cancelled animations.
+    pub fn cancel(&self, animation_id: u32) -> bool {
+        let mut queue = self.queue.borrow_mut();
+        let animation_count = queue.animations.len();
+
+        queue.animations.retain(|a| a.animation_id != animation_id);
+        queue.animations.len() != animation_count
+    }
+
+    /// Indicates whether or not any animations are running.
+    pub fn is_active(&self) -> bool {
+        !self.queue.borrow().animations.is_empty()
+    }
+
+    /// Advances all running animations to the time `now`, applying their properties to the
+    /// `Widget`s in the `cache`.  Returns the animation ID and `Widget` ID of each animation that
+    /// has finished, which are removed from the list.
+    pub(crate) fn advance(&self, now: u128, cache: &mut WidgetCache) -> Vec<(u32, u32)> {
+        let mut queue = self.queue.borrow_mut();
+        let mut finished: Vec<(u32, u32)> = Vec::new();
+
+        for animation in queue.animations.iter_mut() {
+            let start = *animation.start.get_or_insert(now);
+            let elapsed = now.saturating_sub(start);
+            let t = if animation.duration_ms == 0 {
+                1.0
+            } else {
+                (elapsed as f64 / animation.duration_ms as f64).min(1.0)
+            };
+
+            animation
+                .property
+                .apply(animation.widget_id, animation.easing.apply(t), cache);
+
+            if t >= 1.0 {
+                finished.push((animation.animation_id, animation.widget_id));
+            }
+        }
+
+        queue
+            .animations
+            .retain(|a| !finished.iter().any(|(id, _)| *id == a.animation_id));
+
+        finished
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pushrod_widgets::properties::PROPERTY_PROGRESS;
+    use pushrod_widgets::system_widgets::box_widget::BoxWidget;
+
+    const EASINGS: [Easing; 4] = [
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+    ];
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_easing_end_points() {
+        for easing in EASINGS.iter() {
+            assert_close(easing.apply(0.0), 0.0);
+            assert_close(easing.apply(1.0), 1.0);
+        }
+    }
+
+    #[test]
+    fn test_easing_clamps_progress() {
+        for easing in EASINGS.iter() {
+            assert_close(easing.apply(-0.5), 0.0);
+            assert_close(easing.apply(1.5), 1.0);
+        }
+    }
+
+    #[test]
+    fn test_easing_curves() {
+        assert_close(Easing::Linear.apply(0.25), 0.25);
+        assert_close(Easing::EaseIn.apply(0.25), 0.0625);
+        assert_close(Easing::EaseOut.apply(0.25), 0.4375);
+        assert_close(Easing::EaseInOut.apply(0.25), 0.125);
+        assert_close(Easing::EaseInOut.apply(0.5), 0.5);
+        assert_close(Easing::EaseInOut.apply(0.75), 0.875);
+    }
+
+    #[test]
+    fn test_easing_is_monotonic() {
+        for easing in EASINGS.iter() {
+            let mut previous = 0.0;
+
+            for step in 1..=100 {
+                let value = easing.apply(f64::from(step) / 100.0);
+
+                assert!(value >= previous, "{:?} decreased at step {}", easing, step);
+                previous = value;
+            }
+        }
+    }
+
+    /// Creates a `WidgetCache` containing a single `Widget`, returning it along with its ID.
+    fn cache_with_widget() -> (WidgetCache, u32) {
+        let mut cache = WidgetCache::new(400, 400);
+        let widget_id = cache.add(Box::new(BoxWidget::default()), String::from("box"), 0);
+
+        (cache, widget_id)
+    }
+
+    /// Returns the animated `PROPERTY_PROGRESS` value of the `Widget`, as it is stored.
+    fn progress(cache: &WidgetCache, widget_id: u32) -> String {
+        cache.get(widget_id).properties().get(PROPERTY_PROGRESS)
+    }
+
+    /// Animates `PROPERTY_PROGRESS` from 0 to 100 over `duration_ms`.
+    fn animate_progress(animations: &Animations, widget_id: u32, duration_ms: u128) -> u32 {
+        animations.animate(
+            widget_id,
+            AnimatedProperty::Value {
+                property_key: PROPERTY_PROGRESS,
+                from: 0,
+                to: 100,
+            },
+            duration_ms,
+            Easing::Linear,
+        )
+    }
+
+    #[test]
+    fn test_starts_on_first_frame() {
+        let (mut cache, widget_id) = cache_with_widget();
+        let animations = Animations::default();
+        let animation_id = animate_progress(&animations, widget_id, 100);
+
+        // The first frame may come long after the animation was added, but it starts from there.
+        assert!(animations.advance(1000, &mut cache).is_empty());
+        assert_eq!(progress(&cache, widget_id), "0");
+
+        assert!(animations.advance(1050, &mut cache).is_empty());
+        assert_eq!(progress(&cache, widget_id), "50");
+
+        assert_eq!(
+            animations.advance(1100, &mut cache),
+            vec![(animation_id, widget_id)]
+        );
+        assert_eq!(progress(&cache, widget_id), "100");
+        assert!(!animations.is_active());
+        assert!(animations.advance(1200, &mut cache).is_empty());
+    }
+
+    #[test]
+    fn test_zero_duration() {
+        let (mut cache, widget_id) = cache_with_widget();
+        let animations = Animations::default();
+        let animation_id = animate_progress(&animations, widget_id, 0);
+
+        assert_eq!(
+            animations.advance(10, &mut cache),
+            vec![(animation_id, widget_id)]
+        );
+        assert_eq!(progress(&cache, widget_id), "100");
+        assert!(!animations.is_active());
+    }
+
+    #[test]
+    fn test_finished_removed() {
+        let (mut cache, widget_id) = cache_with_widget();
+        let animations = Animations::default();
+        let short_id = animate_progress(&animations, widget_id, 10);
+        let long_id = animations.animate(
+            widget_id,
+            AnimatedProperty::Origin {
+                from: (0, 0),
+                to: (100, 100),
+            },
+            100,
+            Easing::Linear,
+        );
+
+        animations.advance(0, &mut cache);
+
+        assert_eq!(
+            animations.advance(10, &mut cache),
+            vec![(short_id, widget_id)]
+        );
+        assert!(animations.is_active());
+        assert!(!animations.cancel(short_id));
+        assert_eq!(
+            animations.advance(100, &mut cache),
+            vec![(long_id, widget_id)]
+        );
+        assert!(!animations.is_active());
+    }
+
+    #[test]
+    fn test_cancel() {
+        let (mut cache, widget_id) = cache_with_widget();
+        let animations = Animations::default();
+        let animation_id = animate_progress(&animations, widget_id, 100);
+
+        animations.advance(0, &mut cache);
+        animations.advance(20, &mut cache);
+
+        assert_eq!(progress(&cache, widget_id), "20");
+        assert!(animations.cancel(animation_id));
+        assert!(!animations.cancel(animation_id));
+        assert!(!animations.is_active());
+
+        // The property is left where it was, and no finished animation is reported.
+        assert!(animations.advance(200, &mut cache).is_empty());
+        assert_eq!(progress(&cache, widget_id), "20");
+    }
+}
@@ -0,0 +1,265 @@
+// Pushrod Application
+// Multi-Window Run Loop
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use sdl2::{Sdl, VideoSubsystem};
+
+use crate::config::EngineConfig;
+use crate::engine::{Engine, EventHandler};
+use crate::error::{Error, Result};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+/// This is a function that creates a new application `Window`, along with the `EventHandler` that
+/// handles its events, and the `EngineConfig` for its `Engine`.  It is called by the `Application`
+/// run loop when a window is opened through an `ApplicationHandle`.  If it returns an error, the
+/// `Application`'s run loop stops with that error.
+pub type WindowBuilder =
+    Box<dyn FnOnce(&VideoSubsystem) -> Result<(Box<dyn EventHandler>, Window, EngineConfig)>>;
+
+/// These are requests to change the set of open windows, queued up by `ApplicationHandle`s, and
+/// processed by the `Application` after each frame.
+enum WindowRequest {
+    Open(WindowBuilder),
+    Close(u32),
+}
+
+/// This is a handle to an `Application`, which can be cloned and stored inside of an
+/// `EventHandler`, allowing windows to be opened and closed while the `Application` is running.
+/// Requests are processed at the end of the current frame.
+#[derive(Clone, Default)]
+pub struct ApplicationHandle {
+    requests: Rc<RefCell<Vec<WindowRequest>>>,
+}
+
+/// This is an implementation of the `ApplicationHandle`.
+impl ApplicationHandle {
+    /// Requests that a new window be opened.  The `builder` is called with the `VideoSubsystem`,
+    /// and returns the `EventHandler`, the `Window` that it handles, and the `EngineConfig` for
+    /// its `Engine`.
+    pub fn open_window<F>(&self, builder: F)
+    where
+        F: FnOnce(&VideoSubsystem) -> Result<(Box<dyn EventHandler>, Window, EngineConfig)>
+            + 'static,
+    {
+        self.requests
+            .borrow_mut()
+            .push(WindowRequest::Open(Box::new(builder)));
+    }
+
+    /// Requests that the window with the `SDL2` window ID `window_id` be closed.  The window is
+    /// closed without consulting its `EventHandler`'s `close_requested` callback.
+    pub fn close_window(&self, window_id: u32) {
+        self.requests
+            .borrow_mut()
+            .push(WindowRequest::Close(window_id));
+    }
+}
+
+/// This is an open application window: the `Engine` that handles it, and the `Canvas` to which it
+/// is drawn.
+struct ApplicationWindow {
+    window_id: u32,
+    engine: Engine,
+    canvas: Canvas<Window>,
+}
+
+/// This is a run loop for applications with more than one window.  It owns an `Engine` for each
+/// open window, along with the single `SDL2` event pump, and routes each `SDL2` event to the
+/// `Engine` that handles the window in which the event occurred.  Events that are not associated
+/// with any window (such as `Quit`) are sent to all `Engine`s.
+///
+/// The run loop ends when the last window has been closed.
+pub struct Application {
+    sdl: Sdl,
+    video: VideoSubsystem,
+    windows: Vec<ApplicationWindow>,
+    handle: ApplicationHandle,
+}
+
+/// This is an implementation of the `Application`.
+impl Application {
+    /// Creates a new `Application`, taking ownership of the `SDL2` context.  Returns
+    /// `Error::Video` if the video subsystem could not be initialized.
+    pub fn new(sdl: Sdl) -> Result<Self> {
+        let video = sdl.video().map_err(Error::Video)?;
+
+        Ok(Self {
+            sdl,
+            video,
+            windows: Vec::new(),
+            handle: ApplicationHandle::default(),
+        })
+    }
+
+    /// Retrieves the `VideoSubsystem`, which is used to create new `Window`s.
+    pub fn get_video(&self) -> &VideoSubsystem {
+        &self.video
+    }
+
+    /// Retrieves an `ApplicationHandle`, which can be used to open and close windows while the
+    /// `Application` is running.
+    pub fn handle(&self) -> ApplicationHandle {
+        self.handle.clone()
+    }
+
+    /// Opens a new window, creating an `Engine` for it with the `EventHandler` that handles its
+    /// events, and the `EngineConfig` that controls how it runs.  The handler's `build_layout` is
+    /// called immediately.  Returns the `SDL2` window ID of the newly opened window, or an error if
+    /// its `Engine` or renderer could not be created.
+    pub fn open_window(
+        &mut self,
+        handler: Box<dyn EventHandler>,
+        window: Window,
+        config: EngineConfig,
+    ) -> Result<u32> {
+        let window_id = window.id();
+        let mut engine = Engine::new(handler, &window, config)?;
+        let canvas = engine.create_canvas(window)?;
+
+        engine.start();
+
+        self.windows.push(ApplicationWindow {
+            window_id,
+            engine,
+            canvas,
+        });
+
+        Ok(window_id)
+    }
+
+    /// Closes the window with the `SDL2` window ID `window_id`, dropping its `Engine`.
+    pub fn close_window(&mut self, window_id: u32) {
+        self.windows.retain(|w| w.window_id != window_id);
+    }
+
+    /// Retrieves the `Engine` that handles the window with the `SDL2` window ID `window_id`, if
+    /// the window is open.
+    pub fn get_engine(&mut self, window_id: u32) -> Option<&mut Engine> {
+        self.windows
+            .iter_mut()
+            .find(|w| w.window_id == window_id)
+            .map(|w| &mut w.engine)
+    }
+
+    /// Processes any window requests that were queued through `ApplicationHandle`s.
+    fn handle_window_requests(&mut self) -> Result<()> {
+        let requests: Vec<WindowRequest> = self.handle.requests.borrow_mut().drain(..).collect();
+
+        for request in requests {
+            match request {
+                WindowRequest::Open(builder) => {
+                    let (handler, window, config) = builder(&self.video)?;
+
+                    self.open_window(handler, window, config)?;
+                }
+
+                WindowRequest::Close(window_id) => self.close_window(window_id),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Routes an `SDL2` event to the `Engine` that handles the window in which it occurred, or to
+    /// all `Engine`s if it is not associated with a window.
+    fn route_event(&mut self, event: sdl2::event::Event) {
+        match event.get_window_id() {
+            Some(window_id) => {
+                if let Some(engine) = self.get_engine(window_id) {
+                    engine.handle_sdl_event(event);
+                }
+            }
+
+            None => {
+                for window in self.windows.iter_mut() {
+                    window.engine.handle_sdl_event(event.clone());
+                }
+            }
+        }
+    }
+
+    /// Returns the number of milliseconds for which the run loop can block waiting for events, or
+    /// `None` if it should not block.  It only blocks when every `Engine` runs in
+    /// `LoopMode::EventDriven` and is idle, and then only until the earliest idle timeout or timer
+    /// of all of the `Engine`s.
+    fn idle_wait_ms(&self) -> Option<u32> {
+        self.windows
+            .iter()
+            .map(|w| w.engine.idle_wait_ms())
+            .collect::<Option<Vec<u32>>>()
+            .and_then(|timeouts| timeouts.into_iter().min())
+    }
+
+    /// This is the main event loop for the application.  Each `SDL2` event is routed to the
+    /// `Engine` that handles the window in which it occurred, after which every `Engine` processes
+    /// a frame.  `Engine`s that have stopped have their windows closed.
+    ///
+    /// If every `Engine` runs in `LoopMode::EventDriven` and has nothing to animate, the loop blocks
+    /// waiting for events, as a single `Engine` does.  Otherwise, frames are processed at the
+    /// highest frame rate of all of the windows.
+    ///
+    /// Returns an error if the `SDL2` event pump has already been taken, or if a window that was
+    /// requested through an `ApplicationHandle` could not be opened.
+    pub fn run(&mut self) -> Result<()> {
+        let mut event_pump = self.sdl.event_pump().map_err(Error::EventPump)?;
+
+        loop {
+            let start = Instant::now();
+
+            // When all of the windows are idle in event driven mode, block until an event arrives,
+            // or until the first of them needs a frame.
+            if let Some(timeout_ms) = self.idle_wait_ms() {
+                if let Some(event) = event_pump.wait_event_timeout(timeout_ms) {
+                    self.route_event(event);
+                }
+            }
+
+            for event in event_pump.poll_iter() {
+                self.route_event(event);
+            }
+
+            for window in self.windows.iter_mut() {
+                window.engine.process_frame(&mut window.canvas);
+            }
+
+            self.windows.retain(|w| w.engine.is_running());
+            self.handle_window_requests()?;
+
+            if self.windows.is_empty() {
+                break;
+            }
+
+            // And pause the CPU if required to keep the highest frame rate of all of the windows.
+            let fps_as_ms = self
+                .windows
+                .iter()
+                .map(|w| w.engine.frame_ms())
+                .min()
+                .unwrap();
+            let frame_duration = Duration::from_millis(fps_as_ms as u64);
+            let elapsed = start.elapsed();
+
+            if elapsed < frame_duration {
+                sleep(frame_duration - elapsed);
+            }
+        }
+
+        Ok(())
+    }
+}
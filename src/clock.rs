@@ -0,0 +1,74 @@
+// Pushrod Clock
+// Time Sources
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// This is a source of time for the `Engine`.  The time returned is the amount of time elapsed
+/// since the clock was started, and must never go backwards.  It is used to generate the
+/// timestamps of `DrawFrame` events, and the time between frames.
+pub trait Clock {
+    /// Returns the amount of time that has elapsed since the clock was started.
+    fn now(&self) -> Duration;
+}
+
+/// This is the default `Clock`, which is based on the system's monotonic clock.  Unlike the wall
+/// clock, it is not affected by changes to the system time.
+pub struct MonotonicClock {
+    start: Instant,
+}
+
+/// The `MonotonicClock` starts counting from the time it is created.
+impl Default for MonotonicClock {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Clock for MonotonicClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// This is a `Clock` that only advances when told to, so that time can be controlled
+/// deterministically, such as in tests.  It can be cloned, and all clones share the same time, so
+/// one copy can be given to the `Engine`, and another used to advance it.
+#[derive(Clone, Default)]
+pub struct ManualClock {
+    now: Rc<Cell<Duration>>,
+}
+
+/// This is an implementation of the `ManualClock`.
+impl ManualClock {
+    /// Advances the clock by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+
+    /// Sets the clock to `now`.  This must not be earlier than the current time.
+    pub fn set(&self, now: Duration) {
+        self.now.set(now.max(self.now.get()));
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+}
@@ -0,0 +1,112 @@
+// Pushrod Engine Configuration
+// Configuration Builder
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use sdl2::pixels::Color;
+
+use crate::engine::LoopMode;
+
+/// This is the configuration that is passed to an `Engine` when it is created.  It controls the
+/// frame rate, how the `Window`'s renderer is created, and how input is interpreted.
+/// It is created using the builder functions, starting from the default configuration:
+///
+/// ```rust,no_run
+/// use pushrod::config::EngineConfig;
+/// use sdl2::pixels::Color;
+///
+/// let config = EngineConfig::default()
+///     .frame_rate(30)
+///     .vsync(true)
+///     .clear_color(Color::GREY);
+/// ```
+#[derive(Clone, Debug)]
+pub struct EngineConfig {
+    pub(crate) frame_rate: u32,
+    pub(crate) loop_mode: LoopMode,
+    pub(crate) vsync: bool,
+    pub(crate) accelerated: bool,
+    pub(crate) clear_color: Option<Color>,
+    pub(crate) drag_threshold: u32,
+    pub(crate) layout_poll_ms: u32,
+}
+
+/// The default configuration runs at 60 frames/sec in `LoopMode::FixedRate`, using an accelerated
+/// renderer without vsync, with a drag threshold of 4 pixels, checking watched layout files for
+/// changes every 500ms.
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self {
+            frame_rate: 60,
+            loop_mode: LoopMode::FixedRate,
+            vsync: false,
+            accelerated: true,
+            clear_color: None,
+            drag_threshold: 4,
+            layout_poll_ms: 500,
+        }
+    }
+}
+
+/// This is an implementation of the `EngineConfig` builder.
+impl EngineConfig {
+    /// Sets the target number of frames drawn per second.  Values of 0 are treated as 1.
+    pub fn frame_rate(mut self, frame_rate: u32) -> Self {
+        self.frame_rate = frame_rate.max(1);
+        self
+    }
+
+    /// Sets the `LoopMode` that is used to pace the drawing of frames.
+    pub fn loop_mode(mut self, loop_mode: LoopMode) -> Self {
+        self.loop_mode = loop_mode;
+        self
+    }
+
+    /// Sets whether or not drawing is synchronized with the display's refresh rate.
+    pub fn vsync(mut self, vsync: bool) -> Self {
+        self.vsync = vsync;
+        self
+    }
+
+    /// Sets whether an accelerated (GPU-based) renderer is used, or a software renderer.
+    pub fn accelerated(mut self, accelerated: bool) -> Self {
+        self.accelerated = accelerated;
+        self
+    }
+
+    /// Sets the background color of the `Window`.  This is the main color of the top-level
+    /// `Widget`, which is drawn behind all other `Widget`s.
+    pub fn clear_color(mut self, clear_color: Color) -> Self {
+        self.clear_color = Some(clear_color);
+        self
+    }
+
+    /// Sets the distance in pixels that the mouse must move while a button is held before a
+    /// drag-and-drop operation is started.
+    pub fn drag_threshold(mut self, drag_threshold: u32) -> Self {
+        self.drag_threshold = drag_threshold;
+        self
+    }
+
+    /// Sets how often, in milliseconds, a layout file watched using `Engine::watch_layout` is
+    /// checked for changes.
+    pub fn layout_poll_ms(mut self, layout_poll_ms: u32) -> Self {
+        self.layout_poll_ms = layout_poll_ms;
+        self
+    }
+
+    /// Returns the number of milliseconds allotted to each frame at the configured frame rate.
+    pub(crate) fn frame_ms(&self) -> u128 {
+        (1000.0 / self.frame_rate as f64) as u128
+    }
+}
@@ -13,16 +13,60 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use sdl2::event::WindowEvent;
+use sdl2::keyboard::{Keycode, Mod, Scancode};
+use sdl2::mouse::MouseWheelDirection;
+use sdl2::render::Canvas;
 use sdl2::video::Window;
 use sdl2::Sdl;
 
+use crate::animation::Animations;
+use crate::clock::{Clock, MonotonicClock};
+use crate::config::EngineConfig;
+use crate::error::{Error, Result};
+use crate::event::EngineEvent;
+use crate::layout::{LayoutDocument, LayoutIds, LayoutRemap};
+use crate::properties::{
+    PROPERTY_ANIMATING, PROPERTY_DRAG_PAYLOAD, PROPERTY_DROP_TARGET, PROPERTY_EVENT_CONSUMED,
+    PROPERTY_FOCUSABLE, PROPERTY_FOCUSED, PROPERTY_LAYOUT_ON_RESIZE, PROPERTY_RECEIVE_BUBBLED,
+    PROPERTY_SCROLLABLE, PROPERTY_TAB_INDEX,
+};
+use crate::rebroadcast::RebroadcastPolicy;
+use crate::recorder::{InputEvent, InputRecorder, InputReplay};
+use crate::registry::WidgetRegistry;
+use crate::timer::Timers;
+use log::{debug, error, trace, warn};
 use pushrod_widgets::caches::WidgetCache;
-use pushrod_widgets::event::PushrodEvent::{DrawFrame, WidgetRadioSelected};
+use pushrod_widgets::event::PushrodEvent::DrawFrame;
 use pushrod_widgets::event::{Event, PushrodEvent};
-use pushrod_widgets::properties::PROPERTY_NEEDS_LAYOUT;
+use pushrod_widgets::properties::{
+    PROPERTY_DISABLED, PROPERTY_HIDDEN, PROPERTY_MAIN_COLOR, PROPERTY_NEEDS_LAYOUT,
+};
 use pushrod_widgets::widget::Widget;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::thread::sleep;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Keyboard modifiers that prevent `Tab` from being used for focus traversal, leaving the key
+/// combination to be handled by the application.
+const FOCUS_IGNORED_MODS: Mod = Mod::from_bits_truncate(
+    Mod::LCTRLMOD.bits() | Mod::RCTRLMOD.bits() | Mod::LALTMOD.bits() | Mod::RALTMOD.bits(),
+);
+
+/// This is the mode that is used by the `Engine`'s `run` loop to pace the drawing of frames.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LoopMode {
+    /// Wakes up for every frame at the configured frame rate, sending a `DrawFrame` event to every
+    /// `Widget`, whether or not anything has changed.  This is the default.
+    FixedRate,
+
+    /// Blocks waiting for events while nothing is animating, waking up at most every
+    /// `idle_timeout_ms` milliseconds to send a `DrawFrame` event.  While the `EventHandler` or any
+    /// `Widget` requests continuous frames, the `Engine` runs at the configured frame rate.  The
+    /// `Engine` also wakes up in time for any scheduled timers.
+    EventDriven { idle_timeout_ms: u32 },
+}
 
 /// This is an event handler that is passed into a main event loop.  Since there can be multiple
 /// windows open at any one time, the event handler that is implemented using this `trait` should
@@ -40,13 +84,124 @@ pub trait EventHandler {
     /// If this method is not implemented, it does not have any effect on the main application.
     fn handle_event(&mut self, _event: Event, _cache: &mut WidgetCache) {}
 
+    /// This is the event handler for `EngineEvent`s, which are events generated by the `Engine`
+    /// that cannot be represented as a `PushrodEvent`, such as keyboard input.  These are only
+    /// delivered to the handler, never to the `Widget`s themselves.  Events that refer to a
+    /// `Widget` contain the ID of the `Widget` they are intended for, such as the focused
+    /// `Widget` for keyboard input, so that the handler can act upon it through the `cache`.
+    ///
+    /// If this method is not implemented, it does not have any effect on the main application.
+    fn handle_engine_event(&mut self, _event: EngineEvent, _cache: &mut WidgetCache) {}
+
     /// This callback is used when the screen needs to be built for the first time.  It is called
     /// by the `Engine`'s `run` method before the event loop starts.  The `cache` is sent such that
     /// `Widget`s can be added to the display list by using the `WidgetCache`'s functions.
     ///
+    /// The layout can also be loaded from a `.rsc` layout resource file using
+    /// `layout::load_layout`, which returns the IDs of the `Widget`s that it added by name.
+    ///
     /// This function **must** be implemented, as it creates a layout for the application `Window`
     /// upon creation.
     fn build_layout(&mut self, cache: &mut WidgetCache);
+
+    /// This callback is used when a layout file is first loaded by `Engine::watch_layout`, with the
+    /// IDs of the `Widget`s that it added, by name.  This allows the handler to keep track of the
+    /// `Widget`s that it needs to interact with.
+    ///
+    /// If this method is not implemented, the IDs are not stored by the application.
+    fn layout_loaded(&mut self, _ids: &LayoutIds, _cache: &mut WidgetCache) {}
+
+    /// This callback is used when a watched layout file has changed in a way that required its
+    /// `Widget`s to be replaced, such as when a `Widget` was added, removed, or moved to another
+    /// parent.  The new `Widget`s reuse the IDs of the previous ones where they can: a `Widget`
+    /// keeps its ID if its parent did not change, and any IDs that are not reused are hidden.
+    /// `remap` maps the ID of each previous `Widget` to the ID of the `Widget` with the same name in
+    /// the new layout, if there is one, and `ids` contains the IDs of all of the `Widget`s in the
+    /// new layout, by name.  Since IDs are reused, an ID that is not in `remap` may now refer to
+    /// a different `Widget`.
+    ///
+    /// If the layout only changed the properties of its `Widget`s, they are updated in place, and
+    /// this callback is not used, as their IDs do not change.
+    ///
+    /// If this method is not implemented, the handler keeps referring to the previous IDs.
+    fn layout_remapped(
+        &mut self,
+        _remap: &LayoutRemap,
+        _ids: &LayoutIds,
+        _cache: &mut WidgetCache,
+    ) {
+    }
+
+    /// This callback is used by the `Engine` when it runs in `LoopMode::EventDriven`, to determine
+    /// whether or not frames need to be drawn continuously.  Return `true` while the application
+    /// is animating, so that a `DrawFrame` event is sent for every frame.  Once this returns
+    /// `false`, the `Engine` goes back to waiting for events.
+    ///
+    /// If this method is not implemented, the application never requests continuous frames.
+    fn is_animating(&self) -> bool {
+        false
+    }
+
+    /// This callback is used when a dragged `Widget` is dropped onto a drop target, to decide whether
+    /// or not the drop is accepted.  `source_widget_id` is the `Widget` that was dragged, carrying
+    /// the `payload` from its `PROPERTY_DRAG_PAYLOAD`, and `target_widget_id` is the drop target.
+    /// The result is sent to the handler in the `Dropped` event.
+    ///
+    /// If this method is not implemented, all drops are accepted.
+    fn accept_drop(
+        &mut self,
+        _source_widget_id: u32,
+        _target_widget_id: u32,
+        _payload: &str,
+        _cache: &mut WidgetCache,
+    ) -> bool {
+        true
+    }
+
+    /// This callback is used when the mouse wheel is scrolled over `widget_id`, which is the
+    /// `Widget` under the pointer, or one of its parents that has `PROPERTY_SCROLLABLE` set.  `x`
+    /// and `y` are the number of steps scrolled, where positive values scroll right and away from
+    /// the user.  Returning `true` consumes the scroll.  Otherwise, it is offered to the next
+    /// scrollable parent in turn, ending with the top-level `Widget`.
+    ///
+    /// If this method is not implemented, no scroll is consumed.
+    fn mouse_scrolled(
+        &mut self,
+        _widget_id: u32,
+        _x: i32,
+        _y: i32,
+        _cache: &mut WidgetCache,
+    ) -> bool {
+        false
+    }
+
+    /// This callback is used when the user or the system requests that the application `Window`
+    /// be closed.  Returning `true` allows the `Window` to close, stopping the run loop.  Returning
+    /// `false` vetoes the close, which allows the application to prompt the user (for example,
+    /// to save changes) before the `Window` is closed.
+    ///
+    /// If this method is not implemented, the `Window` is always allowed to close.
+    fn close_requested(&mut self, _cache: &mut WidgetCache) -> bool {
+        true
+    }
+
+    /// This callback is used when the application `Window` has been minimized.  Drawing is paused
+    /// until the `Window` is restored, so any ongoing work can be paused here as well.
+    fn window_minimized(&mut self, _cache: &mut WidgetCache) {}
+
+    /// This callback is used when the application `Window` has been restored after being
+    /// minimized or maximized.
+    fn window_restored(&mut self, _cache: &mut WidgetCache) {}
+
+    /// This callback is used when the application `Window` gains the input focus.
+    fn window_focus_gained(&mut self, _cache: &mut WidgetCache) {}
+
+    /// This callback is used when the application `Window` loses the input focus.
+    fn window_focus_lost(&mut self, _cache: &mut WidgetCache) {}
+
+    /// This callback is used when the application `Window` has been exposed, and needs to be
+    /// redrawn.  The `Engine` redraws the `WidgetCache` automatically after this is called.
+    fn window_exposed(&mut self, _cache: &mut WidgetCache) {}
 }
 
 /// This is a `Pushrod` main loop struct.  All of the members of this object are
@@ -54,9 +209,60 @@ pub trait EventHandler {
 /// events.
 pub struct Engine {
     current_widget_id: u32,
+    captured_widget_id: Option<u32>,
+    pressed_buttons: Vec<u32>,
+    pointer_position: (u32, u32),
+    drag: Option<DragState>,
+    focused_widget_id: u32,
+    focus_tab_held: bool,
     handler: Box<dyn EventHandler>,
     cache: WidgetCache,
     running: bool,
+    close_answered: bool,
+    minimized: bool,
+    window_size: (u32, u32),
+    resize_pending: bool,
+    layout_ids: HashMap<u32, Vec<u32>>,
+    config: EngineConfig,
+    clock: Box<dyn Clock>,
+    last_frame_timestamp: Option<u128>,
+    timers: Timers,
+    animations: Animations,
+    rebroadcast_policy: RebroadcastPolicy,
+    frame_count: u64,
+    recorder: Option<InputRecorder>,
+    replay: Option<InputReplay>,
+    widget_registry: WidgetRegistry,
+    layout_watch: Option<LayoutWatch>,
+}
+
+/// This is a potential or ongoing drag-and-drop operation.  It is created when a mouse button is
+/// pressed, and only becomes a drag (`started`) once the mouse has moved beyond the drag threshold
+/// from the point at which it was pressed.
+struct DragState {
+    source_widget_id: u32,
+    origin: (u32, u32),
+    payload: String,
+    started: bool,
+    target_widget_id: Option<u32>,
+}
+
+/// This is a layout file that is watched for changes, along with the layout that was last loaded
+/// from it, and the IDs of the `Widget`s that were added for it.
+struct LayoutWatch {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    last_poll: u128,
+    document: LayoutDocument,
+    ids: LayoutIds,
+    spare_ids: Vec<u32>,
+}
+
+/// Returns the modification time of the file at `path`, if it can be read.
+fn file_modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
 }
 
 #[derive(Default)]
@@ -67,17 +273,63 @@ pub struct WidgetAddList {
 
 /// This is an implementation of `Pushrod`, the main loop handler.  Multiple `Pushrod`s
 /// can be created for multiple windows if your application provides more than one window
-/// with which to interact.
+/// with which to interact.  Since `run` takes ownership of the `SDL2` event pump, multiple
+/// windows should be run through an `Application`, which shares one event pump between them.
 impl Engine {
     /// Creates a new `Pushrod` run loop, taking a reference to the `EventHandler` that handles
-    /// run loop events for this `Window`.
-    pub fn new(handler: Box<dyn EventHandler>, window: &Window) -> Self {
-        Self {
+    /// run loop events for this `Window`, and the `EngineConfig` that controls how it runs.
+    /// Returns `Error::Window` if the `Window` has no drawable area.
+    pub fn new(
+        handler: Box<dyn EventHandler>,
+        window: &Window,
+        config: EngineConfig,
+    ) -> Result<Self> {
+        let (width, height) = window.size();
+
+        if width == 0 || height == 0 {
+            return Err(Error::Window(format!(
+                "Window size {}x{} has no drawable area",
+                width, height
+            )));
+        }
+
+        let cache = WidgetCache::new(width, height);
+
+        if let Some(clear_color) = config.clear_color {
+            cache
+                .get(0)
+                .properties()
+                .set_color(PROPERTY_MAIN_COLOR, clear_color);
+        }
+
+        Ok(Self {
             current_widget_id: 0,
+            captured_widget_id: None,
+            pressed_buttons: Vec::new(),
+            pointer_position: (0, 0),
+            drag: None,
+            focused_widget_id: 0,
+            focus_tab_held: false,
             handler,
-            cache: WidgetCache::new(window.size().0, window.size().1),
+            cache,
             running: true,
-        }
+            close_answered: false,
+            minimized: false,
+            window_size: window.size(),
+            resize_pending: false,
+            layout_ids: HashMap::new(),
+            config,
+            clock: Box::new(MonotonicClock::default()),
+            last_frame_timestamp: None,
+            timers: Timers::default(),
+            animations: Animations::default(),
+            rebroadcast_policy: RebroadcastPolicy::default(),
+            frame_count: 0,
+            recorder: None,
+            replay: None,
+            widget_registry: WidgetRegistry::default(),
+            layout_watch: None,
+        })
     }
 
     /// Stops the Pushrod run loop.
@@ -85,55 +337,507 @@ impl Engine {
         self.running = false;
     }
 
+    /// Indicates whether or not this `Engine` is still running, or if it has been stopped, either
+    /// by calling `stop`, or by its `Window` being closed.
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Replaces the `Clock` that is used to generate frame timestamps.  By default, the `Engine`
+    /// uses a `MonotonicClock`; a `ManualClock` can be used to control time deterministically.
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.clock = clock;
+        self.last_frame_timestamp = None;
+    }
+
+    /// Retrieves the `Timers` service for this `Engine`.  The returned copy shares its timers with
+    /// the `Engine`, so it can be used to schedule and cancel timers.
+    pub fn timers(&self) -> Timers {
+        self.timers.clone()
+    }
+
+    /// Replaces the `Timers` service for this `Engine`.  This allows an application to create
+    /// `Timers` before the `Engine`, and keep a copy of it inside of its `EventHandler`.
+    pub fn set_timers(&mut self, timers: Timers) {
+        self.timers = timers;
+    }
+
+    /// Retrieves the `Animations` service for this `Engine`.  The returned copy shares its
+    /// animations with the `Engine`, so it can be used to start and cancel animations.
+    pub fn animations(&self) -> Animations {
+        self.animations.clone()
+    }
+
+    /// Replaces the `Animations` service for this `Engine`.  This allows an application to create
+    /// `Animations` before the `Engine`, and keep a copy of it inside of its `EventHandler`.
+    pub fn set_animations(&mut self, animations: Animations) {
+        self.animations = animations;
+    }
+
+    /// Replaces the `RebroadcastPolicy`, which controls how events generated by `Widget`s are
+    /// redistributed to other `Widget`s.  By default, `WidgetRadioSelected` events are sent to all
+    /// `Widget`s.
+    pub fn set_rebroadcast_policy(&mut self, rebroadcast_policy: RebroadcastPolicy) {
+        self.rebroadcast_policy = rebroadcast_policy;
+    }
+
+    /// Replaces the `WidgetRegistry` that is used to create the `Widget`s in layouts loaded by
+    /// `watch_layout`.  By default, only the system `Widget`s can be used.
+    pub fn set_widget_registry(&mut self, widget_registry: WidgetRegistry) {
+        self.widget_registry = widget_registry;
+    }
+
+    /// Loads the layout in the file at `path`, adding its `Widget`s to the `WidgetCache`, and
+    /// watches the file for changes while the `Engine` runs.  The IDs of the `Widget`s that were
+    /// added are sent to the `EventHandler` through `layout_loaded`.  Only one layout can be
+    /// watched at a time; watching another file stops watching the previous one, leaving its
+    /// `Widget`s in place.
+    ///
+    /// The file's modification time is checked every `layout_poll_ms` milliseconds (see
+    /// `EngineConfig`).  When it changes, the layout is reloaded.  If only the properties of its
+    /// `Widget`s changed, they are updated in place.  Otherwise, the `Widget`s from the previous
+    /// layout are hidden, the new ones are added, and the `EventHandler` is told how their IDs were
+    /// remapped through `layout_remapped`.  The state of the `EventHandler` is kept either way.  If
+    /// the changed file cannot be loaded, a warning is logged, and the current layout is kept.
+    ///
+    /// Returns `Error::Io` if the file cannot be read, or `Error::Layout` if it is not a valid
+    /// layout.
+    pub fn watch_layout<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let path = path.as_ref().to_path_buf();
+        let modified = file_modified(&path);
+        let document = LayoutDocument::load(&path)?;
+        let ids = document.apply_with(&mut self.cache, &self.widget_registry)?;
+
+        debug!("Watching layout {}: IDs {:?}", path.display(), ids);
+
+        self.handler.layout_loaded(&ids, &mut self.cache);
+        self.layout_watch = Some(LayoutWatch {
+            path,
+            modified,
+            last_poll: self.clock.now().as_millis(),
+            document,
+            ids,
+            spare_ids: Vec::new(),
+        });
+
+        Ok(())
+    }
+
+    /// Stops watching the layout file given to `watch_layout`, leaving its `Widget`s in place.
+    pub fn stop_watching_layout(&mut self) {
+        self.layout_watch = None;
+    }
+
+    /// Starts recording all input dispatched by this `Engine` to the file at `path`, replacing it
+    /// if it already exists.  Each mouse, key and text input event is written along with the
+    /// number of frames since the recording started, and its time within the frame, so that it can
+    /// be played back later using `replay_input`.  Any recording in progress is stopped first.
+    pub fn record_input<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        self.stop_recording()?;
+        self.recorder = Some(InputRecorder::create(path, self.frame_count)?);
+
+        Ok(())
+    }
+
+    /// Stops recording input, flushing the recording to disk.  Does nothing if no recording is in
+    /// progress.
+    pub fn stop_recording(&mut self) -> Result<()> {
+        if let Some(mut recorder) = self.recorder.take() {
+            recorder.finish()?;
+        }
+
+        Ok(())
+    }
+
+    /// Indicates whether or not input is currently being recorded.
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// Starts playing back the input recorded in the file at `path`.  While the recording is being
+    /// played back, mouse, key and text input from `SDL2` is ignored, and the recorded input is fed
+    /// through the same dispatch functions in its place, on the same frames on which it was
+    /// recorded.  Once all of the input has been played back, a `ReplayFinished` event is sent to
+    /// the `EventHandler`, and input from `SDL2` is accepted again.
+    ///
+    /// Returns `Error::Io` of kind `InvalidData` if the file is not a valid recording.
+    pub fn replay_input<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        self.replay = Some(InputReplay::load(path)?);
+
+        Ok(())
+    }
+
+    /// Indicates whether or not recorded input is currently being played back.
+    pub fn is_replaying(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    /// Injects a synthetic mouse movement to the absolute `x` and `y` coordinates of the `Window`.
+    /// The movement is processed exactly as if it had been received from `SDL2`: the `Widget`
+    /// under the pointer receives the `MouseMoved` event, and `WidgetMouseEntered` and
+    /// `WidgetMouseExited` events are generated as the pointer crosses `Widget`s.
+    pub fn inject_mouse_move(&mut self, x: u32, y: u32) {
+        self.dispatch_input(InputEvent::MouseMoved { x, y });
+    }
+
+    /// Injects a synthetic mouse button press (`state` = `true`) or release (`state` = `false`)
+    /// at the current pointer position.  `button` is the `SDL2` `MouseButton` as a `u32`.
+    pub fn inject_mouse_button(&mut self, button: u32, state: bool) {
+        self.dispatch_input(InputEvent::MouseButton { button, state });
+    }
+
+    /// Injects a synthetic mouse wheel scroll of `x` and `y` steps at the current pointer position,
+    /// where positive values scroll right and away from the user.
+    pub fn inject_mouse_wheel(&mut self, x: i32, y: i32) {
+        self.dispatch_input(InputEvent::MouseWheel { x, y });
+    }
+
+    /// Injects a synthetic key press (`state` = `true`) or release (`state` = `false`), which is
+    /// sent to the `EventHandler` with the ID of the `Widget` that has the keyboard focus.  The
+    /// scan code is derived from the `keycode`.
+    pub fn inject_key(&mut self, keycode: Keycode, keymod: Mod, state: bool) {
+        self.dispatch_input(InputEvent::Key {
+            keycode: Some(keycode),
+            scancode: Scancode::from_keycode(keycode),
+            keymod,
+            repeat: false,
+            state,
+        });
+    }
+
+    /// Injects synthetic text input, which is sent to the `EventHandler` with the ID of the
+    /// `Widget` that has the keyboard focus.
+    pub fn inject_text(&mut self, text: &str) {
+        self.dispatch_input(InputEvent::TextInput {
+            text: String::from(text),
+        });
+    }
+
+    /// Injects a raw `SDL2` event, which is translated and dispatched exactly as if it had been
+    /// received from the event pump.  This can be used for events that have no dedicated injection
+    /// function, such as `Window` events.
+    pub fn inject_sdl_event(&mut self, event: sdl2::event::Event) {
+        self.handle_sdl_event(event);
+    }
+
+    /// Injects a frame tick, processing a single frame exactly as the `run` loop does: the
+    /// `DrawFrame` and `FrameTick` events, any timers and animations that are due, any pending
+    /// layouts, and a redraw of the `canvas` if anything has been invalidated.  The frame's
+    /// timestamp is taken from the `Engine`'s `Clock`, so a `ManualClock` should be used to
+    /// control the passing of time deterministically.
+    pub fn inject_tick(&mut self, canvas: &mut Canvas<Window>) {
+        self.process_frame(canvas);
+    }
+
     /// Retrieves the `WidgetCache`.
     pub fn get_cache(&mut self) -> &mut WidgetCache {
         &mut self.cache
     }
 
+    /// Retrieves the ID of the `Widget` that currently has the keyboard focus.  If no `Widget`
+    /// has been focused, this is the top-level `Widget` (ID 0).
+    pub fn get_focused_widget(&self) -> u32 {
+        self.focused_widget_id
+    }
+
+    /// Sets the keyboard focus to the `Widget` specified by `widget_id`.  All keyboard and text
+    /// input events sent to the `EventHandler` carry the ID of this `Widget` until the focus
+    /// changes.  Passing 0 clears the
+    /// focus.  If the focus changes, a `WidgetFocusLost` event is sent for the previously focused
+    /// `Widget`, followed by a `WidgetFocusGained` event for the newly focused `Widget`; neither is
+    /// sent for ID 0.  The `PROPERTY_FOCUSED` property is moved along with the focus, so that
+    /// `Widget`s can draw their focused state.
+    pub fn set_focused_widget(&mut self, widget_id: u32) {
+        let previous_widget_id = self.focused_widget_id;
+
+        if previous_widget_id == widget_id {
+            return;
+        }
+
+        self.focused_widget_id = widget_id;
+
+        // The top-level Widget (ID 0) stands for no focus, so no events are sent for it.
+        if previous_widget_id != 0 {
+            {
+                let mut previous_widget = self.cache.get(previous_widget_id);
+
+                previous_widget.properties().delete(PROPERTY_FOCUSED);
+                previous_widget.invalidate();
+            }
+
+            self.handler.handle_engine_event(
+                EngineEvent::WidgetFocusLost {
+                    widget_id: previous_widget_id,
+                },
+                &mut self.cache,
+            );
+        }
+
+        if widget_id != 0 {
+            {
+                let mut widget = self.cache.get(widget_id);
+
+                widget.properties().set_bool(PROPERTY_FOCUSED);
+                widget.invalidate();
+            }
+
+            self.handler.handle_engine_event(
+                EngineEvent::WidgetFocusGained { widget_id },
+                &mut self.cache,
+            );
+        }
+    }
+
+    /// Determines whether or not a `Widget` can currently receive the keyboard focus.  The `Widget`
+    /// must be marked as focusable, must not be disabled, and neither it nor any of its parents
+    /// may be hidden.
+    fn is_focusable(&self, widget_id: u32) -> bool {
+        if widget_id == 0 {
+            return false;
+        }
+
+        {
+            let mut widget = self.cache.get(widget_id);
+            let properties = widget.properties();
+
+            if !properties.get_bool(PROPERTY_FOCUSABLE)
+                && properties.get(PROPERTY_TAB_INDEX).is_empty()
+            {
+                return false;
+            }
+
+            if properties.get_bool(PROPERTY_DISABLED) {
+                return false;
+            }
+        }
+
+        !self.is_hidden(widget_id)
+    }
+
+    /// Determines whether or not a `Widget` is hidden, either by `PROPERTY_HIDDEN` being set on the
+    /// `Widget` itself, or on any of its parents.
+    fn is_hidden(&self, widget_id: u32) -> bool {
+        let mut current_id = widget_id;
+
+        loop {
+            if self
+                .cache
+                .get(current_id)
+                .properties()
+                .get_bool(PROPERTY_HIDDEN)
+            {
+                return true;
+            }
+
+            if current_id == 0 {
+                return false;
+            }
+
+            current_id = self.cache.get_parent_of(current_id);
+        }
+    }
+
+    /// Returns the IDs of all `Widget`s that can currently receive focus, in traversal order:
+    /// `Widget`s with a `PROPERTY_TAB_INDEX` in ascending tab index order first, followed by the
+    /// remaining focusable `Widget`s in cache order.
+    fn focus_order(&self) -> Vec<u32> {
+        let mut indexed: Vec<(i32, u32)> = Vec::new();
+        let mut unindexed: Vec<u32> = Vec::new();
+
+        for i in 0..self.cache.size() {
+            if !self.is_focusable(i) {
+                continue;
+            }
+
+            let mut widget = self.cache.get(i);
+
+            if widget.properties().get(PROPERTY_TAB_INDEX).is_empty() {
+                unindexed.push(i);
+            } else {
+                indexed.push((widget.properties().get_value(PROPERTY_TAB_INDEX), i));
+            }
+        }
+
+        // The sort is stable, so Widgets sharing a tab index remain in cache order.
+        indexed.sort_by_key(|(tab_index, _)| *tab_index);
+
+        indexed
+            .into_iter()
+            .map(|(_, widget_id)| widget_id)
+            .chain(unindexed)
+            .collect()
+    }
+
+    /// Moves the keyboard focus to the next focusable `Widget`, or the previous one if `reverse` is
+    /// set, wrapping around at either end of the focus order.
+    pub fn focus_next(&mut self, reverse: bool) {
+        let order = self.focus_order();
+
+        if order.is_empty() {
+            return;
+        }
+
+        let next_widget_id = match order.iter().position(|&id| id == self.focused_widget_id) {
+            Some(position) if reverse => order[(position + order.len() - 1) % order.len()],
+            Some(position) => order[(position + 1) % order.len()],
+            None if reverse => order[order.len() - 1],
+            None => order[0],
+        };
+
+        self.set_focused_widget(next_widget_id);
+    }
+
     /// Broadcasts a generated `PushrodEvent` to the current `Widget`, capturing the response, and
-    /// forwarding it on to the application if an event was returned.
-    fn send_event_to_widget(&mut self, widget_id: u32, event: PushrodEvent) {
+    /// forwarding it on to the application if an event was returned.  The response is then
+    /// redistributed according to the `RebroadcastPolicy`.
+    fn deliver_event(&mut self, widget_id: u32, event: PushrodEvent) {
         let handled_event = self.cache.get(widget_id).handle_event(event);
 
         if let Some(x) = handled_event {
             self.handler
-                .handle_event(Event::Pushrod(x), &mut self.cache)
+                .handle_event(Event::Pushrod(x.clone()), &mut self.cache);
+
+            let handler = &mut self.handler;
+
+            self.rebroadcast_policy.rebroadcast(
+                &mut self.cache,
+                widget_id,
+                x,
+                &mut |cache, target_id, event| {
+                    let handled_event = cache.get(target_id).handle_event(event);
+
+                    if let Some(x) = &handled_event {
+                        handler.handle_event(Event::Pushrod(x.clone()), cache);
+                    }
+
+                    handled_event
+                },
+            );
         }
     }
 
-    /// Sends an event to all `Widget`s.
-    fn send_event_to_all(&mut self, event: PushrodEvent) {
-        let cache_size = self.cache.size();
+    /// Determines whether or not a `Widget` consumed the last event it was sent, by setting
+    /// `PROPERTY_EVENT_CONSUMED`.  The property is cleared, so it applies to one event only.
+    fn take_event_consumed(&mut self, widget_id: u32) -> bool {
+        let mut widget = self.cache.get(widget_id);
+        let consumed = widget.properties().get_bool(PROPERTY_EVENT_CONSUMED);
 
-        for i in 0..cache_size {
-            let handled_event = self.cache.get(i).handle_event(event.clone());
+        if consumed {
+            widget.properties().delete(PROPERTY_EVENT_CONSUMED);
+        }
 
-            if let Some(x) = handled_event {
-                self.handler
-                    .handle_event(Event::Pushrod(x.clone()), &mut self.cache);
-
-                // RESEND the event ONLY IF the event qualifies as a re-distributable event, as the widget's
-                // generated event has already been sent to the handler.  This could potentially cause
-                // an infinite loop, so this needs to be used with care.
-                //
-                // Clippy is complaining about this being only one event.  This will eventually become
-                // multiple events that are handled by a "rebroadcast" flag soon.
-                match x {
-                    WidgetRadioSelected { .. } => self.send_event_to_all(x.clone()),
-                    _ => {}
-                }
+        consumed
+    }
+
+    /// Sends a `PushrodEvent` to a `Widget`.  Pointer input (`MouseMoved` and `MouseButton`) then
+    /// bubbles up through the `Widget`'s parents: each parent that has `PROPERTY_RECEIVE_BUBBLED`
+    /// set receives the same event, until a `Widget` marks it as consumed by setting
+    /// `PROPERTY_EVENT_CONSUMED` while handling it, or the top-level `Widget` is reached.
+    fn send_event_to_widget(&mut self, widget_id: u32, event: PushrodEvent) {
+        let bubbles = matches!(
+            event,
+            PushrodEvent::MouseMoved { .. } | PushrodEvent::MouseButton { .. }
+        );
+
+        self.deliver_event(widget_id, event.clone());
+
+        let mut consumed = self.take_event_consumed(widget_id);
+        let mut parent_id = widget_id;
+
+        while bubbles && !consumed && parent_id != 0 {
+            parent_id = self.cache.get_parent_of(parent_id);
+
+            if self
+                .cache
+                .get(parent_id)
+                .properties()
+                .get_bool(PROPERTY_RECEIVE_BUBBLED)
+            {
+                self.deliver_event(parent_id, event.clone());
+                consumed = self.take_event_consumed(parent_id);
             }
         }
     }
 
+    /// Dispatches a single piece of input to the appropriate handler, writing it to the input
+    /// recording first, if one is in progress.  If the recording cannot be written, it is stopped.
+    pub(crate) fn dispatch_input(&mut self, input: InputEvent) {
+        trace!("Dispatching input: {:?}", input);
+
+        // Timers scheduled in response to input are measured from now, not from the last frame.
+        self.timers.set_now(self.clock.now().as_millis());
+
+        if let Some(recorder) = self.recorder.as_mut() {
+            let offset_ms = match self.last_frame_timestamp {
+                Some(last_frame_timestamp) => self
+                    .clock
+                    .now()
+                    .as_millis()
+                    .saturating_sub(last_frame_timestamp),
+                None => 0,
+            };
+
+            if let Err(e) = recorder.record(self.frame_count, offset_ms, &input) {
+                error!("Input recording stopped: {}", e);
+                self.recorder = None;
+            }
+        }
+
+        match input {
+            InputEvent::MouseMoved { x, y } => self.handle_mouse_move(x, y),
+
+            InputEvent::MouseButton { button, state } => self.handle_mouse_button(button, state),
+
+            InputEvent::Key {
+                keycode,
+                scancode,
+                keymod,
+                repeat,
+                state,
+            } => self.handle_key(keycode, scancode, keymod, repeat, state),
+
+            InputEvent::TextInput { text } => self.handle_text_input(text),
+
+            InputEvent::MouseWheel { x, y } => self.handle_mouse_wheel(x, y),
+        }
+    }
+
+    /// Feeds the input recorded for the current frame through the dispatch functions, if a
+    /// recording is being played back.  Once all of the recorded input has been played back, the
+    /// `EventHandler` is sent a `ReplayFinished` event.
+    fn handle_replay(&mut self) {
+        let inputs = match self.replay.as_mut() {
+            Some(replay) => replay.next_frame(),
+            None => return,
+        };
+
+        for input in inputs {
+            self.dispatch_input(input);
+        }
+
+        if matches!(&self.replay, Some(replay) if replay.is_finished()) {
+            self.replay = None;
+            self.handler
+                .handle_engine_event(EngineEvent::ReplayFinished, &mut self.cache);
+        }
+    }
+
     /// This function handles the `MouseMotion` event, converting it into an `Event` that can be
     /// used by `Pushrod`.  The X and Y coordinates are translated into relative offsets based on the
     /// position of the `Widget`.  This way, the X and Y coordinates can be based on drawing
     /// functions inside the `Widget` if necessary.
-    fn handle_mouse_move(&mut self, x: u32, y: u32) {
+    ///
+    /// While a mouse button is held, the `MouseMoved` event is sent to the `Widget` that captured
+    /// the mouse, even if the pointer has left it.  Offsets to the left of or above the captured
+    /// `Widget` are clamped to 0.  `WidgetMouseEntered` and `WidgetMouseExited` events are still
+    /// generated for the `Widget`s under the pointer, so that the captured `Widget` knows whether
+    /// or not the pointer is over it.
+    pub(crate) fn handle_mouse_move(&mut self, x: u32, y: u32) {
         let cur_widget_id = self.current_widget_id;
 
-        self.current_widget_id = self.cache.id_at_point(x as u32, y as u32);
+        self.pointer_position = (x, y);
+
+        self.current_widget_id = self.cache.id_at_point(x, y);
 
         if cur_widget_id != self.current_widget_id {
             let exited_event = PushrodEvent::WidgetMouseExited {
@@ -155,54 +859,334 @@ impl Engine {
             self.send_event_to_widget(self.current_widget_id, entered_event);
         }
 
-        let points = self
-            .cache
-            .get(self.current_widget_id)
-            .properties()
-            .get_origin();
+        let target_widget_id = self.captured_widget_id.unwrap_or(self.current_widget_id);
+        let points = self.cache.get(target_widget_id).properties().get_origin();
         let event = PushrodEvent::MouseMoved {
-            widget_id: self.current_widget_id,
-            x: (x - points.0),
-            y: (y - points.1),
+            widget_id: target_widget_id,
+            x: x.saturating_sub(points.0),
+            y: y.saturating_sub(points.1),
         };
 
         self.handler
             .handle_event(Event::Pushrod(event.clone()), &mut self.cache);
 
-        self.send_event_to_widget(self.current_widget_id, event);
+        self.send_event_to_widget(target_widget_id, event);
+        self.handle_drag(x, y);
+    }
+
+    /// Returns the ID of the drop target under the pointer: the `Widget` under the pointer, or the
+    /// closest of its parents that has `PROPERTY_DROP_TARGET` set.  The `Widget` being dragged is
+    /// never its own drop target.
+    fn drop_target_at_pointer(&self, source_widget_id: u32) -> Option<u32> {
+        let mut widget_id = self.current_widget_id;
+
+        loop {
+            if widget_id != source_widget_id
+                && self
+                    .cache
+                    .get(widget_id)
+                    .properties()
+                    .get_bool(PROPERTY_DROP_TARGET)
+            {
+                return Some(widget_id);
+            }
+
+            if widget_id == 0 {
+                return None;
+            }
+
+            widget_id = self.cache.get_parent_of(widget_id);
+        }
+    }
+
+    /// Drives a drag-and-drop operation as the mouse moves to `x` and `y`.  Once the mouse has
+    /// moved beyond the drag threshold while a button is held over a `Widget` that has a
+    /// `PROPERTY_DRAG_PAYLOAD`, a `DragStarted` event is sent.  As the drag moves over drop targets,
+    /// `DragOver` and `DragLeave` events are sent.
+    fn handle_drag(&mut self, x: u32, y: u32) {
+        let (source_widget_id, previous_target_id) = match self.drag.as_mut() {
+            Some(drag) => {
+                if !drag.started {
+                    let distance = (x as i64 - drag.origin.0 as i64)
+                        .abs()
+                        .max((y as i64 - drag.origin.1 as i64).abs());
+
+                    if distance < self.config.drag_threshold as i64 {
+                        return;
+                    }
+                }
+
+                (drag.source_widget_id, drag.target_widget_id)
+            }
+
+            None => return,
+        };
+
+        if !self.drag.as_ref().unwrap().started {
+            let payload = self
+                .cache
+                .get(source_widget_id)
+                .properties()
+                .get(PROPERTY_DRAG_PAYLOAD);
+
+            // Widgets without a payload cannot be dragged.
+            if payload.is_empty() {
+                self.drag = None;
+                return;
+            }
+
+            let drag = self.drag.as_mut().unwrap();
+
+            drag.started = true;
+            drag.payload = payload.clone();
+
+            self.handler.handle_engine_event(
+                EngineEvent::DragStarted {
+                    widget_id: source_widget_id,
+                    payload,
+                },
+                &mut self.cache,
+            );
+        }
+
+        let target_widget_id = self.drop_target_at_pointer(source_widget_id);
+
+        if target_widget_id != previous_target_id {
+            if let Some(previous_target_id) = previous_target_id {
+                self.handler.handle_engine_event(
+                    EngineEvent::DragLeave {
+                        widget_id: previous_target_id,
+                        source_widget_id,
+                    },
+                    &mut self.cache,
+                );
+            }
+
+            self.drag.as_mut().unwrap().target_widget_id = target_widget_id;
+        }
+
+        if let Some(target_widget_id) = target_widget_id {
+            let points = self.cache.get(target_widget_id).properties().get_origin();
+
+            self.handler.handle_engine_event(
+                EngineEvent::DragOver {
+                    widget_id: target_widget_id,
+                    source_widget_id,
+                    x: x.saturating_sub(points.0),
+                    y: y.saturating_sub(points.1),
+                },
+                &mut self.cache,
+            );
+        }
+    }
+
+    /// Ends a drag-and-drop operation when the mouse buttons are released.  If the drag is over a
+    /// drop target, the `EventHandler`'s `accept_drop` callback decides whether the drop is
+    /// accepted, and a `Dropped` event is sent.  Otherwise, a `DragCancelled` event is sent.
+    fn handle_drop(&mut self) {
+        let drag = match self.drag.take() {
+            Some(drag) if drag.started => drag,
+            _ => return,
+        };
+
+        let event = match drag.target_widget_id {
+            Some(target_widget_id) => {
+                let accepted = self.handler.accept_drop(
+                    drag.source_widget_id,
+                    target_widget_id,
+                    &drag.payload,
+                    &mut self.cache,
+                );
+
+                EngineEvent::Dropped {
+                    widget_id: target_widget_id,
+                    source_widget_id: drag.source_widget_id,
+                    payload: drag.payload,
+                    accepted,
+                }
+            }
+
+            None => EngineEvent::DragCancelled {
+                source_widget_id: drag.source_widget_id,
+            },
+        };
+
+        self.handler.handle_engine_event(event, &mut self.cache);
     }
 
     /// Handles a `MouseButton` event, which indicates that a mouse button has been pressed or released.
-    fn handle_mouse_button(&mut self, mouse_button: u32, state: bool) {
+    ///
+    /// The first button pressed captures the mouse for the `Widget` under the pointer.  Until all
+    /// buttons have been released, motion and button events are sent only to that `Widget`, so the
+    /// release is always delivered to the `Widget` that received the press, and no other.  Releasing
+    /// the last button also ends any drag-and-drop operation that is in progress.
+    pub(crate) fn handle_mouse_button(&mut self, mouse_button: u32, state: bool) {
+        if state {
+            if self.captured_widget_id.is_none() {
+                self.captured_widget_id = Some(self.current_widget_id);
+                self.drag = Some(DragState {
+                    source_widget_id: self.current_widget_id,
+                    origin: self.pointer_position,
+                    payload: String::new(),
+                    started: false,
+                    target_widget_id: None,
+                });
+            }
+
+            if !self.pressed_buttons.contains(&mouse_button) {
+                self.pressed_buttons.push(mouse_button);
+            }
+        }
+
+        let widget_id = self.captured_widget_id.unwrap_or(self.current_widget_id);
         let event = PushrodEvent::MouseButton {
-            widget_id: self.current_widget_id,
+            widget_id,
             button: mouse_button,
             state,
         };
 
+        if !state {
+            self.pressed_buttons.retain(|b| *b != mouse_button);
+
+            if self.pressed_buttons.is_empty() {
+                self.captured_widget_id = None;
+            }
+        }
+
         self.handler
             .handle_event(Event::Pushrod(event.clone()), &mut self.cache);
 
-        if !state {
-            self.send_event_to_all(event);
-        } else {
-            self.send_event_to_widget(self.current_widget_id, event);
+        // Pressing a mouse button over a focusable Widget gives it the keyboard focus.
+        if state && self.is_focusable(widget_id) {
+            self.set_focused_widget(widget_id);
+        }
+
+        self.send_event_to_widget(widget_id, event);
+
+        if self.captured_widget_id.is_none() {
+            self.handle_drop();
         }
     }
 
+    /// Handles a `MouseWheel` event, offering the scroll to the `EventHandler`'s `mouse_scrolled`
+    /// callback for the `Widget` under the pointer.  As with pointer input, the scroll bubbles up
+    /// through the `Widget`'s parents until it is consumed: it is offered for each `Widget` in the
+    /// chain that has `PROPERTY_SCROLLABLE` set, until the callback returns `true`.  If no `Widget`
+    /// consumes it, it is finally offered for the top-level `Widget`.
+    pub(crate) fn handle_mouse_wheel(&mut self, x: i32, y: i32) {
+        let mut widget_id = self.current_widget_id;
+
+        loop {
+            let scrollable = widget_id == 0
+                || self
+                    .cache
+                    .get(widget_id)
+                    .properties()
+                    .get_bool(PROPERTY_SCROLLABLE);
+
+            if scrollable
+                && self
+                    .handler
+                    .mouse_scrolled(widget_id, x, y, &mut self.cache)
+            {
+                break;
+            }
+
+            if widget_id == 0 {
+                break;
+            }
+
+            widget_id = self.cache.get_parent_of(widget_id);
+        }
+    }
+
+    /// Retrieves the ID of the `Widget` that has captured the mouse, if a mouse button is held.
+    pub fn get_captured_widget(&self) -> Option<u32> {
+        self.captured_widget_id
+    }
+
+    /// Handles a `KeyDown` or `KeyUp` event, sending it to the `EventHandler` as a `KeyPressed` or
+    /// `KeyReleased` event, carrying the ID of the `Widget` that has the keyboard focus.  `Widget`s
+    /// do not receive keyboard input directly; the handler acts on the focused `Widget` instead.
+    pub(crate) fn handle_key(
+        &mut self,
+        keycode: Option<Keycode>,
+        scancode: Option<Scancode>,
+        keymod: Mod,
+        repeat: bool,
+        state: bool,
+    ) {
+        // Tab and Shift-Tab move the focus, and are not sent on to the focused Widget.  The release
+        // of a Tab that moved the focus is not sent either, so that the newly focused Widget does
+        // not receive a release without a matching press.
+        if keycode == Some(Keycode::Tab) {
+            if state && !keymod.intersects(FOCUS_IGNORED_MODS) {
+                self.focus_tab_held = true;
+                self.focus_next(keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD));
+                return;
+            }
+
+            if !state && self.focus_tab_held {
+                self.focus_tab_held = false;
+                return;
+            }
+        }
+
+        let widget_id = self.focused_widget_id;
+        let event = if state {
+            EngineEvent::KeyPressed {
+                widget_id,
+                keycode,
+                scancode,
+                keymod,
+                repeat,
+            }
+        } else {
+            EngineEvent::KeyReleased {
+                widget_id,
+                keycode,
+                scancode,
+                keymod,
+            }
+        };
+
+        self.handler.handle_engine_event(event, &mut self.cache);
+    }
+
+    /// Handles a `TextInput` event, sending the entered text to the `EventHandler`, carrying the ID
+    /// of the `Widget` that has the keyboard focus.
+    pub(crate) fn handle_text_input(&mut self, text: String) {
+        let event = EngineEvent::TextInput {
+            widget_id: self.focused_widget_id,
+            text,
+        };
+
+        self.handler.handle_engine_event(event, &mut self.cache);
+    }
+
     /// Handles a draw frame event.  This is a timer tick event that can be used by an application
     /// to refresh positions, redraw 3D objects, etc.  It provides a display tick so that the
-    /// application can refresh at a rate of 60 frames/sec.
+    /// application can refresh at the configured frame rate.  The `timestamp` is the number of
+    /// milliseconds since the `Engine`'s `Clock` started, and `delta` is the number of milliseconds
+    /// since the previous frame.  The `EventHandler` also receives a `FrameTick` event containing
+    /// both.  Running animations are advanced after the `Widget`s receive the `DrawFrame` event.
+    /// Hidden `Widget`s do not receive the `DrawFrame` event.
     #[inline]
-    fn handle_draw_frame(&mut self, timestamp: u128) {
+    fn handle_draw_frame(&mut self, timestamp: u128, delta: u128) {
         let event = DrawFrame { timestamp };
 
+        self.handler
+            .handle_engine_event(EngineEvent::FrameTick { timestamp, delta }, &mut self.cache);
+
         self.handler
             .handle_event(Event::Pushrod(event.clone()), &mut self.cache);
 
-        let widget_count = self.cache.size();
+        // Hidden Widgets, such as those left over from a rebuilt layout, are skipped.
+        let visible_ids: Vec<u32> = (0..self.cache.size())
+            .filter(|i| !self.is_hidden(*i))
+            .collect();
 
-        for i in 0..widget_count {
+        for i in visible_ids {
             let handled_event = self.cache.get(i).handle_event(event.clone());
 
             if let Some(x) = handled_event {
@@ -210,6 +1194,17 @@ impl Engine {
                     .handle_event(Event::Pushrod(x), &mut self.cache)
             }
         }
+
+        // Advance any running animations, now that the application has seen this frame.
+        for (animation_id, widget_id) in self.animations.advance(timestamp, &mut self.cache) {
+            self.handler.handle_engine_event(
+                EngineEvent::AnimationFinished {
+                    animation_id,
+                    widget_id,
+                },
+                &mut self.cache,
+            );
+        }
     }
 
     /// This function handles the building of additional `Widget`s to the `WidgetCache` if a newly
@@ -246,97 +1241,532 @@ impl Engine {
         for addable in add_list {
             let widget_list = addable.add_list;
             let parent_id = addable.parent_id;
-            let resulting_ids = self.cache.add_vec(widget_list, parent_id);
 
-            eprintln!("IDs: {:?}", resulting_ids);
+            // If this Widget has been laid out before (for example, after a Window resize), the
+            // IDs from its previous layout are reused for the new Widgets, so that the cache does
+            // not grow every time the layout is rebuilt.  New IDs are only added when the layout
+            // has more Widgets than ever before, and any IDs that are not needed are hidden, and
+            // kept for reuse.
+            let previous_ids = self.layout_ids.remove(&parent_id).unwrap_or_default();
+            let reused_count = previous_ids.len().min(widget_list.len());
+            let mut new_widgets = widget_list.into_iter();
+            let mut resulting_ids: Vec<u32> = Vec::new();
+
+            for previous_id in previous_ids.iter().take(reused_count) {
+                let widget = self.cache.get_mut(*previous_id);
+
+                *widget = new_widgets.next().unwrap();
+                widget.invalidate();
+                resulting_ids.push(*previous_id);
+            }
+
+            for spare_id in previous_ids.iter().skip(reused_count) {
+                self.cache.set_hidden(*spare_id, true);
+            }
 
+            resulting_ids.extend(self.cache.add_vec(new_widgets.collect(), parent_id));
+
+            debug!(
+                "Built layout for widget {}: IDs {:?}",
+                parent_id, resulting_ids
+            );
+
+            let mut layout_ids = resulting_ids.clone();
+
+            layout_ids.extend(previous_ids.iter().skip(reused_count));
+            self.layout_ids.insert(parent_id, layout_ids);
             self.cache
                 .get_mut(parent_id)
-                .constructed_layout_ids(resulting_ids);
+                .constructed_layout_ids(resulting_ids.clone());
+            self.handler.handle_engine_event(
+                EngineEvent::LayoutBuilt {
+                    widget_id: parent_id,
+                    child_ids: resulting_ids,
+                },
+                &mut self.cache,
+            );
         }
     }
 
-    /// This is the main event handler for the application.  It handles all of the events generated
-    /// by the `SDL2` manager, and translates them into events that can be used by the `handle_event`
-    /// method.
-    pub fn run(&mut self, sdl: Sdl, window: Window) {
-        let mut event_pump = sdl.event_pump().unwrap();
-        let fps_as_ms = (1000.0 / 60_f64) as u128;
-        let mut canvas = window
-            .into_canvas()
-            .target_texture()
-            .accelerated()
-            .build()
-            .unwrap();
+    /// Checks the watched layout file for changes, if it is due to be checked, reloading it if it
+    /// has been modified since it was last loaded.
+    fn handle_layout_watch(&mut self, timestamp: u128) {
+        let poll_ms = self.config.layout_poll_ms as u128;
+        let mut watch = match self.layout_watch.take() {
+            Some(watch) => watch,
+            None => return,
+        };
 
-        // Call handler.build_layout() - this allows the application to build its `Window` contents,
-        // preparing the application for use.  (This is where the deserialization will occur.)
-        self.handler.build_layout(&mut self.cache);
+        if timestamp.saturating_sub(watch.last_poll) >= poll_ms {
+            let modified = file_modified(&watch.path);
 
-        'running: loop {
-            let start = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_millis();
+            watch.last_poll = timestamp;
 
-            // Process events first
-            for event in event_pump.poll_iter() {
-                match event {
-                    // TODO Change this to a callback that allows the application to handle a
-                    // TODO closure of a window.  Returning true breaks the loop, false does not.
-                    sdl2::event::Event::Quit { .. } => break 'running,
+            if modified != watch.modified {
+                watch.modified = modified;
+                self.reload_layout(&mut watch);
+            }
+        }
 
-                    sdl2::event::Event::MouseMotion { x, y, .. } => {
-                        self.handle_mouse_move(x as u32, y as u32);
-                    }
+        self.layout_watch = Some(watch);
+    }
 
-                    sdl2::event::Event::MouseButtonDown { mouse_btn, .. } => {
-                        self.handle_mouse_button(mouse_btn as u32, true);
-                    }
+    /// Reloads a watched layout file, updating its `Widget`s in place if the layout still describes
+    /// the same `Widget`s, or replacing them if it does not.  Replaced `Widget`s reuse the IDs of
+    /// the previous ones where they can, so the `WidgetCache` only grows when the layout has more
+    /// `Widget`s under a parent than it has had before.
+    fn reload_layout(&mut self, watch: &mut LayoutWatch) {
+        let document = match LayoutDocument::load(&watch.path) {
+            Ok(document) => document,
+            Err(e) => {
+                warn!("Unable to reload layout {}: {}", watch.path.display(), e);
+                return;
+            }
+        };
 
-                    sdl2::event::Event::MouseButtonUp { mouse_btn, .. } => {
-                        self.handle_mouse_button(mouse_btn as u32, false);
-                    }
+        if document.has_same_widgets(&watch.document) {
+            debug!("Reloaded layout {} in place", watch.path.display());
+
+            document.update(&watch.document, &watch.ids, &self.cache);
+            watch.document = document;
+            return;
+        }
+
+        let ids = match document.replace(
+            &mut self.cache,
+            &self.widget_registry,
+            &watch.ids,
+            &mut watch.spare_ids,
+        ) {
+            Ok(ids) => ids,
+            Err(e) => {
+                warn!("Unable to reload layout {}: {}", watch.path.display(), e);
+                return;
+            }
+        };
+        let mut remap = LayoutRemap::new();
+
+        for (name, previous_id) in watch.ids.iter() {
+            if let Some(widget_id) = ids.get(name) {
+                remap.insert(*previous_id, *widget_id);
+            }
+        }
+
+        self.cache.get(0).invalidate();
+
+        debug!(
+            "Reloaded layout {}: IDs {:?}, remapped {:?}",
+            watch.path.display(),
+            ids,
+            remap
+        );
+
+        self.handler.layout_remapped(&remap, &ids, &mut self.cache);
+
+        // Any interaction with the replaced Widgets is moved to their replacements, or ends.
+        let previous_ids: Vec<u32> = watch.ids.values().copied().collect();
+
+        if previous_ids.contains(&self.current_widget_id) {
+            self.current_widget_id = 0;
+        }
+
+        if matches!(self.captured_widget_id, Some(widget_id) if previous_ids.contains(&widget_id)) {
+            self.captured_widget_id = None;
+        }
+
+        if matches!(&self.drag, Some(drag) if previous_ids.contains(&drag.source_widget_id)) {
+            self.drag = None;
+        }
+
+        if previous_ids.contains(&self.focused_widget_id) {
+            let focused_widget_id = match remap.get(&self.focused_widget_id) {
+                Some(widget_id) if self.is_focusable(*widget_id) => *widget_id,
+                _ => 0,
+            };
+
+            if focused_widget_id == self.focused_widget_id {
+                // The focused Widget was replaced under the same ID, so it keeps the focus.
+                let mut widget = self.cache.get(focused_widget_id);
+
+                widget.properties().set_bool(PROPERTY_FOCUSED);
+                widget.invalidate();
+            } else {
+                self.set_focused_widget(focused_widget_id);
+            }
+        }
+
+        watch.document = document;
+        watch.ids = ids;
+    }
 
-                    unhandled_event => eprintln!("Event: {:?}", unhandled_event),
+    /// Translates an `SDL2` event into the `Event`s that are used by `Pushrod`, and dispatches them
+    /// to the `Widget`s and the `EventHandler`.  This is called by the `run` loop for each event
+    /// received from the event pump.
+    pub(crate) fn handle_sdl_event(&mut self, event: sdl2::event::Event) {
+        self.timers.set_now(self.clock.now().as_millis());
+
+        match event {
+            // Closing the last Window sends a window Close event followed by Quit, so a Quit that
+            // follows a close request that was already answered in this frame is ignored.
+            sdl2::event::Event::Quit { .. } => {
+                if !self.close_answered {
+                    self.handle_close_requested();
                 }
             }
 
-            // Tick event
-            self.handle_draw_frame(
-                SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis(),
+            sdl2::event::Event::Window { win_event, .. } => self.handle_window_event(win_event),
+
+            // Input from SDL2 is ignored while a recording is being played back in its place.
+            sdl2::event::Event::MouseMotion { .. }
+            | sdl2::event::Event::MouseButtonDown { .. }
+            | sdl2::event::Event::MouseButtonUp { .. }
+            | sdl2::event::Event::KeyDown { .. }
+            | sdl2::event::Event::KeyUp { .. }
+            | sdl2::event::Event::TextInput { .. }
+            | sdl2::event::Event::MouseWheel { .. }
+                if self.replay.is_some() => {}
+
+            sdl2::event::Event::MouseMotion { x, y, .. } => {
+                self.dispatch_input(InputEvent::MouseMoved {
+                    x: x as u32,
+                    y: y as u32,
+                });
+            }
+
+            sdl2::event::Event::MouseButtonDown { mouse_btn, .. } => {
+                self.dispatch_input(InputEvent::MouseButton {
+                    button: mouse_btn as u32,
+                    state: true,
+                });
+            }
+
+            sdl2::event::Event::MouseButtonUp { mouse_btn, .. } => {
+                self.dispatch_input(InputEvent::MouseButton {
+                    button: mouse_btn as u32,
+                    state: false,
+                });
+            }
+
+            sdl2::event::Event::KeyDown {
+                keycode,
+                scancode,
+                keymod,
+                repeat,
+                ..
+            } => {
+                self.dispatch_input(InputEvent::Key {
+                    keycode,
+                    scancode,
+                    keymod,
+                    repeat,
+                    state: true,
+                });
+            }
+
+            sdl2::event::Event::KeyUp {
+                keycode,
+                scancode,
+                keymod,
+                ..
+            } => {
+                self.dispatch_input(InputEvent::Key {
+                    keycode,
+                    scancode,
+                    keymod,
+                    repeat: false,
+                    state: false,
+                });
+            }
+
+            sdl2::event::Event::TextInput { text, .. } => {
+                self.dispatch_input(InputEvent::TextInput { text });
+            }
+
+            // Flipped wheels (such as "natural" scrolling) report deltas in the opposite
+            // direction, so they are normalized here.
+            sdl2::event::Event::MouseWheel {
+                x, y, direction, ..
+            } => {
+                let (x, y) = match direction {
+                    MouseWheelDirection::Flipped => (-x, -y),
+                    _ => (x, y),
+                };
+
+                self.dispatch_input(InputEvent::MouseWheel { x, y });
+            }
+
+            unhandled_event => {
+                trace!("Unhandled event: {:?}", unhandled_event);
+            }
+        }
+    }
+
+    /// Handles a request to close the `Window`, either from the window manager, or from the
+    /// application quitting.  The `EventHandler` is asked via `close_requested` whether the close
+    /// is allowed; if so, the run loop stops after the current frame.  The handler is asked at most
+    /// once per frame, so that a single close of the last `Window` does not prompt it twice.
+    fn handle_close_requested(&mut self) {
+        self.close_answered = true;
+
+        if self.handler.close_requested(&mut self.cache) {
+            self.stop();
+        }
+    }
+
+    /// Handles a `WindowEvent`, forwarding lifecycle changes to the `EventHandler`.
+    fn handle_window_event(&mut self, event: WindowEvent) {
+        match event {
+            WindowEvent::Close => self.handle_close_requested(),
+
+            WindowEvent::Minimized => {
+                self.minimized = true;
+                self.handler.window_minimized(&mut self.cache);
+            }
+
+            WindowEvent::Restored => {
+                self.minimized = false;
+                self.cache.get(0).invalidate();
+                self.handler.window_restored(&mut self.cache);
+            }
+
+            // SDL2 sends both of these events when the user resizes the Window, and many of them
+            // while the Window is being dragged to a new size, so only the last change in size in
+            // each frame is handled, when the frame is processed.
+            WindowEvent::Resized(width, height) | WindowEvent::SizeChanged(width, height) => {
+                if (width as u32, height as u32) != self.window_size {
+                    self.window_size = (width as u32, height as u32);
+                    self.resize_pending = true;
+                }
+            }
+
+            WindowEvent::FocusGained => self.handler.window_focus_gained(&mut self.cache),
+
+            WindowEvent::FocusLost => self.handler.window_focus_lost(&mut self.cache),
+
+            WindowEvent::Exposed => {
+                self.cache.get(0).invalidate();
+                self.handler.window_exposed(&mut self.cache);
+            }
+
+            unhandled_event => {
+                trace!("Unhandled window event: {:?}", unhandled_event);
+            }
+        }
+    }
+
+    /// Handles a change in the size of the `Window`, once per frame in which the size changed.  The
+    /// bounds of the top-level `Widget` are updated to the new size, and a `WindowResized` event is
+    /// sent to the `EventHandler`, which can reposition or resize its `Widget`s accordingly.  Any
+    /// `Widget`s that have `PROPERTY_LAYOUT_ON_RESIZE` set are then flagged with
+    /// `PROPERTY_NEEDS_LAYOUT`, so that their layout is rebuilt before the next redraw.  No event is
+    /// sent to the `Widget`s themselves; any other `Widget` that depends on the size of the
+    /// `Window` must be updated by the handler.
+    fn handle_resize(&mut self) {
+        let (width, height) = self.window_size;
+
+        debug!("Window resized to {}x{}", width, height);
+
+        {
+            let mut root_widget = self.cache.get(0);
+
+            root_widget.properties().set_bounds(width, height);
+            root_widget.invalidate();
+        }
+
+        self.handler.handle_engine_event(
+            EngineEvent::WindowResized { width, height },
+            &mut self.cache,
+        );
+
+        for i in 0..self.cache.size() {
+            let mut widget = self.cache.get(i);
+
+            if widget.properties().get_bool(PROPERTY_LAYOUT_ON_RESIZE) {
+                widget.properties().set_bool(PROPERTY_NEEDS_LAYOUT);
+                widget.invalidate();
+            }
+        }
+    }
+
+    /// Returns the number of milliseconds allotted to each frame at the configured frame rate.
+    pub(crate) fn frame_ms(&self) -> u128 {
+        self.config.frame_ms()
+    }
+
+    /// Returns the number of milliseconds for which the run loop can block waiting for events
+    /// before processing the next frame, or `None` if it should not block.  This is only the case
+    /// in `LoopMode::EventDriven` while nothing needs continuous frames.
+    pub(crate) fn idle_wait_ms(&self) -> Option<u32> {
+        match self.config.loop_mode {
+            LoopMode::EventDriven { idle_timeout_ms } if !self.needs_continuous_frames() => {
+                Some(self.idle_timeout_ms(idle_timeout_ms))
+            }
+
+            _ => None,
+        }
+    }
+
+    /// Determines whether or not frames need to be drawn continuously, rather than only in response
+    /// to events.  This is the case when any `Animations` are running, when the `EventHandler`
+    /// reports that it is animating, or when any `Widget` has `PROPERTY_ANIMATING` set.
+    fn needs_continuous_frames(&self) -> bool {
+        if self.animations.is_active() || self.handler.is_animating() {
+            return true;
+        }
+
+        (0..self.cache.size()).any(|i| self.cache.get(i).properties().get_bool(PROPERTY_ANIMATING))
+    }
+
+    /// Returns the number of milliseconds to wait for events while idle: the `idle_timeout_ms`, or
+    /// less if a timer is due to fire sooner.
+    fn idle_timeout_ms(&self, idle_timeout_ms: u32) -> u32 {
+        match self.timers.next_deadline() {
+            Some(deadline) => {
+                let now = self.clock.now().as_millis();
+
+                deadline.saturating_sub(now).min(idle_timeout_ms as u128) as u32
+            }
+
+            None => idle_timeout_ms,
+        }
+    }
+
+    /// Converts the `Window` into the `Canvas` to which this `Engine` draws, using the renderer
+    /// settings from the `EngineConfig`.  Returns `Error::Renderer` if the renderer could not be
+    /// created.
+    pub(crate) fn create_canvas(&self, window: Window) -> Result<Canvas<Window>> {
+        let mut builder = window.into_canvas().target_texture();
+
+        builder = if self.config.accelerated {
+            builder.accelerated()
+        } else {
+            builder.software()
+        };
+
+        if self.config.vsync {
+            builder = builder.present_vsync();
+        }
+
+        let mut canvas = builder
+            .build()
+            .map_err(|e| Error::Renderer(e.to_string()))?;
+
+        if let Some(clear_color) = self.config.clear_color {
+            canvas.set_draw_color(clear_color);
+            canvas.clear();
+        }
+
+        Ok(canvas)
+    }
+
+    /// Calls the handler's `build_layout` - this allows the application to build its `Window`
+    /// contents, preparing the application for use, either directly, or by loading a layout.
+    pub(crate) fn start(&mut self) {
+        self.handler.build_layout(&mut self.cache);
+    }
+
+    /// Processes a single frame: sends the tick event, builds any pending layouts, and draws
+    /// the `WidgetCache` to the `canvas` if anything has been invalidated.  The frame's timestamp is
+    /// taken from the `Engine`'s `Clock`.  This is shared by
+    /// the `run` loop and the `HeadlessEngine`, so both exercise the same drawing logic.
+    pub(crate) fn process_frame(&mut self, canvas: &mut Canvas<Window>) {
+        let timestamp = self.clock.now().as_millis();
+        let delta = match self.last_frame_timestamp {
+            Some(last_frame_timestamp) => timestamp.saturating_sub(last_frame_timestamp),
+            None => 0,
+        };
+
+        self.last_frame_timestamp = Some(timestamp);
+
+        // Recorded input is played back before the frame, as if it had come from the event pump.
+        self.handle_replay();
+
+        // Tick event
+        self.handle_draw_frame(timestamp, delta);
+
+        // Reload the watched layout file if it has changed.
+        self.handle_layout_watch(timestamp);
+
+        // Fire any timers that are due.
+        for (timer_id, widget_id) in self.timers.expire(timestamp) {
+            self.handler.handle_engine_event(
+                EngineEvent::TimerFired {
+                    timer_id,
+                    widget_id,
+                },
+                &mut self.cache,
             );
+        }
+
+        // Resizing is handled once per frame, using the last size that the Window was given.
+        if self.resize_pending {
+            self.resize_pending = false;
+            self.handle_resize();
+        }
+
+        // Any Widgets that need a layout can be handled here.
+        if self.cache.needs_layout() {
+            debug!("Building layouts");
+
+            self.handle_build_layout();
+        }
+
+        // Draw the screen if any widgets have been invalidated or added to the display list.
+        // There is nothing to see while the Window is minimized, so drawing is skipped.
+        if !self.minimized && self.cache.invalidated() {
+            // Draw after events are processed.
+            trace!("Redrawing frame {}", self.frame_count);
+            self.cache.refresh(canvas);
+        }
 
-            // Any Widgets that need a layout can be handled here.
-            if self.cache.needs_layout() {
-                eprintln!("Needs layout");
-                self.handle_build_layout();
+        self.close_answered = false;
+        self.frame_count += 1;
+    }
+
+    /// This is the main event handler for the application.  It handles all of the events generated
+    /// by the `SDL2` manager, and translates them into events that can be used by the `handle_event`
+    /// method.
+    ///
+    /// Returns an error if the `SDL2` event pump has already been taken, or if the renderer could
+    /// not be created.
+    pub fn run(&mut self, sdl: Sdl, window: Window) -> Result<()> {
+        let mut event_pump = sdl.event_pump().map_err(Error::EventPump)?;
+        let frame_duration = Duration::from_millis(self.frame_ms() as u64);
+        let mut canvas = self.create_canvas(window)?;
+
+        self.start();
+
+        'running: loop {
+            let start = Instant::now();
+
+            // When idle in event driven mode, block until an event arrives (or the idle timeout
+            // expires), rather than waking up for every frame.
+            if let Some(timeout_ms) = self.idle_wait_ms() {
+                if let Some(event) = event_pump.wait_event_timeout(timeout_ms) {
+                    self.handle_sdl_event(event);
+                }
             }
 
-            // Draw the screen if any widgets have been invalidated or added to the display list
-            if self.cache.invalidated() {
-                // Draw after events are processed.
-                self.cache.refresh(&mut canvas);
+            // Process events first
+            for event in event_pump.poll_iter() {
+                self.handle_sdl_event(event);
             }
 
-            // And pause the CPU if required to keep the system at 60 fps.
-            let now = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_millis();
+            self.process_frame(&mut canvas);
 
-            if now - start < fps_as_ms {
-                let diff = fps_as_ms - (now - start);
+            // And pause the CPU if required to keep the configured frame rate.  In event driven mode,
+            // any time spent waiting for events counts toward the frame.
+            let elapsed = start.elapsed();
 
-                sleep(Duration::from_millis(diff as u64));
+            if elapsed < frame_duration {
+                sleep(frame_duration - elapsed);
             }
 
             if !self.running {
                 break 'running;
             }
         }
+
+        Ok(())
     }
 }
@@ -0,0 +1,76 @@
+// Pushrod Errors
+// Error and Result Types
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::layout::LayoutError;
+use std::fmt;
+
+/// This is an error that can occur while creating or running an `Engine`.  Errors reported by
+/// `SDL2` are stored as the message that `SDL2` provided.
+///
+/// Fonts and images are loaded by the `Widget`s in `pushrod_widgets` while they draw, so failures to
+/// load them are not reported here.
+#[derive(Debug)]
+pub enum Error {
+    /// The `SDL2` video subsystem could not be initialized.
+    Video(String),
+
+    /// A `Window` could not be created, or is not usable.
+    Window(String),
+
+    /// The renderer for a `Window` could not be created, or drawing failed.
+    Renderer(String),
+
+    /// The `SDL2` event pump could not be obtained.  Only one event pump can exist at a time, so
+    /// this happens if it has already been taken, such as by another `Engine`.
+    EventPump(String),
+
+    /// A file could not be read or written.
+    Io(std::io::Error),
+
+    /// A layout could not be loaded, such as from a malformed `.rsc` file, or could not be saved.
+    Layout(LayoutError),
+}
+
+/// This is a `Result` whose error type is the `pushrod` `Error`.
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Video(message) => write!(f, "Unable to initialize video: {}", message),
+            Error::Window(message) => write!(f, "Unable to create window: {}", message),
+            Error::Renderer(message) => write!(f, "Renderer error: {}", message),
+            Error::EventPump(message) => write!(f, "Unable to obtain event pump: {}", message),
+            Error::Io(error) => write!(f, "I/O error: {}", error),
+            Error::Layout(error) => write!(f, "Layout error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            Error::Layout(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
@@ -0,0 +1,73 @@
+// Pushrod Escaping
+// Single Line Text Escaping
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// Escapes backslashes and line breaks in `text`, so that it fits on a single line of a file.
+/// Line breaks are written as `\n` and `\r`, and backslashes as `\\`.
+pub(crate) fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// Reverses `escape_text`.  A backslash followed by any other character is replaced by that
+/// character, and a trailing backslash is kept.
+pub(crate) fn unescape_text(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for text in &[
+            "",
+            "plain",
+            "  padded  ",
+            "a\\nb",
+            "line\nbreaks\r\n",
+            "end\\",
+        ] {
+            let escaped = escape_text(text);
+
+            assert!(!escaped.contains('\n') && !escaped.contains('\r'));
+            assert_eq!(unescape_text(&escaped), *text);
+        }
+    }
+
+    #[test]
+    fn test_unescape() {
+        assert_eq!(unescape_text("a\\\\n\\nb"), "a\\n\nb");
+        assert_eq!(unescape_text("\\x\\"), "x\\");
+    }
+}
@@ -0,0 +1,119 @@
+// Pushrod Events
+// Engine Events
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use sdl2::keyboard::{Keycode, Mod, Scancode};
+
+/// These are events that are generated by the `Engine` itself, rather than by a `Widget`.  They
+/// are only delivered to the `EventHandler`, through its `handle_engine_event` method; `Widget`s
+/// never receive them.  Events that refer to a `Widget` carry the ID of the `Widget` they are
+/// intended for, so that the handler can act upon it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum EngineEvent {
+    /// Indicates that a key was pressed while `widget_id` had the keyboard focus.  `repeat` is set
+    /// when the event was generated by the key being held down.
+    KeyPressed {
+        widget_id: u32,
+        keycode: Option<Keycode>,
+        scancode: Option<Scancode>,
+        keymod: Mod,
+        repeat: bool,
+    },
+
+    /// Indicates that a key was released while `widget_id` had the keyboard focus.
+    KeyReleased {
+        widget_id: u32,
+        keycode: Option<Keycode>,
+        scancode: Option<Scancode>,
+        keymod: Mod,
+    },
+
+    /// Indicates that text was entered while `widget_id` had the keyboard focus.  This is the
+    /// translated text (after keyboard layout and input method processing), so it should be used
+    /// for text entry instead of `KeyPressed`.
+    TextInput { widget_id: u32, text: String },
+
+    /// Indicates that `widget_id` has received the keyboard focus.
+    WidgetFocusGained { widget_id: u32 },
+
+    /// Indicates that `widget_id` has lost the keyboard focus.
+    WidgetFocusLost { widget_id: u32 },
+
+    /// Indicates that the `Window` has been resized to `width` x `height`.  The bounds of the
+    /// top-level `Widget` have already been updated when this is received.  Other `Widget`s are
+    /// not notified, apart from having their layout rebuilt if they set
+    /// `PROPERTY_LAYOUT_ON_RESIZE`.
+    WindowResized { width: u32, height: u32 },
+
+    /// Indicates that the layout of `widget_id` was built, or rebuilt (such as after a resize, for
+    /// a `Widget` with `PROPERTY_LAYOUT_ON_RESIZE`), and that its layout now consists of the
+    /// `Widget`s in `child_ids`.  When a layout is rebuilt, the IDs of its previous `Widget`s are
+    /// reused for the new ones, so IDs that were stored for the previous layout may now refer to
+    /// different `Widget`s.
+    LayoutBuilt { widget_id: u32, child_ids: Vec<u32> },
+
+    /// Sent at the start of every frame, along with the `DrawFrame` event.  `timestamp` is the
+    /// number of milliseconds since the `Engine`'s `Clock` started, and `delta` is the number of
+    /// milliseconds since the previous frame (0 for the first frame).
+    FrameTick { timestamp: u128, delta: u128 },
+
+    /// Indicates that the timer with the ID `timer_id` has fired.  If the timer was scheduled
+    /// with a target `Widget`, its ID is provided in `widget_id`.
+    TimerFired {
+        timer_id: u32,
+        widget_id: Option<u32>,
+    },
+
+    /// Indicates that the animation with the ID `animation_id`, which animated a property of
+    /// `widget_id`, has finished.
+    AnimationFinished { animation_id: u32, widget_id: u32 },
+
+    /// Indicates that `widget_id` has started being dragged, carrying the `payload` from its
+    /// `PROPERTY_DRAG_PAYLOAD`.
+    DragStarted { widget_id: u32, payload: String },
+
+    /// Indicates that the `Widget` being dragged, `source_widget_id`, is over the drop target
+    /// `widget_id`, at the `x` and `y` offsets within it.  This is sent each time the mouse moves.
+    DragOver {
+        widget_id: u32,
+        source_widget_id: u32,
+        x: u32,
+        y: u32,
+    },
+
+    /// Indicates that the `Widget` being dragged, `source_widget_id`, has left the drop target
+    /// `widget_id` without being dropped.
+    DragLeave {
+        widget_id: u32,
+        source_widget_id: u32,
+    },
+
+    /// Indicates that the `Widget` being dragged, `source_widget_id`, has been dropped onto the
+    /// drop target `widget_id`, with its `payload`.  `accepted` is the result of the
+    /// `EventHandler`'s `accept_drop` callback.
+    Dropped {
+        widget_id: u32,
+        source_widget_id: u32,
+        payload: String,
+        accepted: bool,
+    },
+
+    /// Indicates that the `Widget` being dragged, `source_widget_id`, was released somewhere other
+    /// than a drop target, ending the drag.
+    DragCancelled { source_widget_id: u32 },
+
+    /// Indicates that all of the input from a recording started with `Engine::replay_input` has
+    /// been played back, and that input from `SDL2` is being accepted again.
+    ReplayFinished,
+}
@@ -0,0 +1,180 @@
+// Pushrod Headless Engine
+// Offscreen rendering support
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use sdl2::keyboard::{Keycode, Mod};
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use sdl2::{Sdl, VideoSubsystem};
+
+use crate::clock::ManualClock;
+use crate::config::EngineConfig;
+use crate::engine::{Engine, EventHandler};
+use crate::error::{Error, Result};
+use pushrod_widgets::caches::WidgetCache;
+use std::time::Duration;
+
+/// This is a headless `Engine` runner.  Rather than blocking in the `SDL2` event pump and drawing
+/// to an accelerated `Window`, it renders the `WidgetCache` into an in-memory software surface,
+/// allowing frames to be stepped one at a time, synthetic input to be fed in, and the resulting
+/// pixels to be read back.
+///
+/// If the `SDL_VIDEODRIVER` environment variable is not set, the `dummy` video driver is used,
+/// so no display is required.  This makes it suitable for running in CI.
+///
+/// Since `SDL2` is not thread safe, only one `HeadlessEngine` should exist at any one time.  Tests
+/// that use this should be run with `--test-threads=1`.
+pub struct HeadlessEngine {
+    engine: Engine,
+    canvas: Canvas<Window>,
+    clock: ManualClock,
+    _video: VideoSubsystem,
+    _sdl: Sdl,
+}
+
+/// This is an implementation of the `HeadlessEngine`.  All input and frame processing is sent
+/// through the `Engine`'s injection functions, which use the same dispatch functions as its `run`
+/// loop.
+impl HeadlessEngine {
+    /// Creates a new `HeadlessEngine` with an offscreen surface of `width` x `height` pixels,
+    /// taking the `EventHandler` that handles events for this surface.  The handler's
+    /// `build_layout` is called immediately, so the `WidgetCache` is populated upon return.
+    /// Returns an error if `SDL2`, the surface, or its renderer could not be initialized.
+    pub fn new(handler: Box<dyn EventHandler>, width: u32, height: u32) -> Result<Self> {
+        Self::with_config(handler, width, height, EngineConfig::default())
+    }
+
+    /// Creates a new `HeadlessEngine` as with `new`, using the `EngineConfig` specified.  A software
+    /// renderer is always used, regardless of the configuration.
+    pub fn with_config(
+        handler: Box<dyn EventHandler>,
+        width: u32,
+        height: u32,
+        config: EngineConfig,
+    ) -> Result<Self> {
+        if std::env::var_os("SDL_VIDEODRIVER").is_none() {
+            sdl2::hint::set("SDL_VIDEODRIVER", "dummy");
+        }
+
+        let sdl = sdl2::init().map_err(Error::Video)?;
+        let video = sdl.video().map_err(Error::Video)?;
+        let window = video
+            .window("pushrod headless", width, height)
+            .hidden()
+            .build()
+            .map_err(|e| Error::Window(e.to_string()))?;
+        let mut engine = Engine::new(handler, &window, config.accelerated(false))?;
+        let canvas = engine.create_canvas(window)?;
+        let clock = ManualClock::default();
+
+        engine.set_clock(Box::new(clock.clone()));
+        engine.start();
+
+        Ok(Self {
+            engine,
+            canvas,
+            clock,
+            _video: video,
+            _sdl: sdl,
+        })
+    }
+
+    /// Processes a single frame: the draw frame tick, any pending layouts, and a redraw of any
+    /// invalidated `Widget`s.  The `Engine`'s clock is advanced deterministically by the length of
+    /// one frame at the configured frame rate.
+    pub fn step(&mut self) {
+        self.clock
+            .advance(Duration::from_millis(self.engine.frame_ms() as u64));
+        self.engine.inject_tick(&mut self.canvas);
+    }
+
+    /// Processes `frames` frames in succession.
+    pub fn step_frames(&mut self, frames: u32) {
+        for _ in 0..frames {
+            self.step();
+        }
+    }
+
+    /// Feeds a synthetic mouse movement to the absolute `x` and `y` coordinates of the surface.
+    pub fn mouse_move(&mut self, x: u32, y: u32) {
+        self.engine.inject_mouse_move(x, y);
+    }
+
+    /// Feeds a synthetic mouse button press (`state` = `true`) or release (`state` = `false`).
+    pub fn mouse_button(&mut self, button: u32, state: bool) {
+        self.engine.inject_mouse_button(button, state);
+    }
+
+    /// Feeds a synthetic key press (`state` = `true`) or release (`state` = `false`), which the
+    /// `EventHandler` receives with the ID of the `Widget` that has the keyboard focus.
+    pub fn key(&mut self, keycode: Keycode, keymod: Mod, state: bool) {
+        self.engine.inject_key(keycode, keymod, state);
+    }
+
+    /// Feeds synthetic text input, which the `EventHandler` receives with the ID of the `Widget`
+    /// that has the keyboard focus.
+    pub fn text_input(&mut self, text: &str) {
+        self.engine.inject_text(text);
+    }
+
+    /// Reads back the entire surface as a list of `RGBA8888` pixels, packed into `u32` values,
+    /// stored row by row.  Returns `Error::Renderer` if the surface could not be read.
+    pub fn pixels(&self) -> Result<Vec<u32>> {
+        Ok(self
+            .canvas
+            .read_pixels(None, PixelFormatEnum::RGBA8888)
+            .map_err(Error::Renderer)?
+            .chunks_exact(4)
+            .map(|p| u32::from_ne_bytes([p[0], p[1], p[2], p[3]]))
+            .collect())
+    }
+
+    /// Reads back the `Color` of a single pixel at the `x` and `y` coordinates of the surface.
+    /// Returns `Error::Renderer` if the surface could not be read.
+    pub fn pixel_at(&self, x: u32, y: u32) -> Result<Color> {
+        let bytes = self
+            .canvas
+            .read_pixels(
+                Rect::new(x as i32, y as i32, 1, 1),
+                PixelFormatEnum::RGBA8888,
+            )
+            .map_err(Error::Renderer)?;
+        let pixel = u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+
+        Ok(Color::RGBA(
+            (pixel >> 24) as u8,
+            (pixel >> 16) as u8,
+            (pixel >> 8) as u8,
+            pixel as u8,
+        ))
+    }
+
+    /// Retrieves the `ManualClock` used by the `Engine`, which can be advanced between frames to
+    /// simulate the passing of time.
+    pub fn get_clock(&self) -> &ManualClock {
+        &self.clock
+    }
+
+    /// Retrieves the `Engine` that is driven by this runner.
+    pub fn get_engine(&mut self) -> &mut Engine {
+        &mut self.engine
+    }
+
+    /// Retrieves the `WidgetCache`.
+    pub fn get_cache(&mut self) -> &mut WidgetCache {
+        self.engine.get_cache()
+    }
+}
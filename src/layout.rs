@@ -0,0 +1,1287 @@
+// Pushrod Layout
+// Layout Resource Loader
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::{Error, Result};
+use crate::escape::{escape_text, unescape_text};
+use crate::properties::{
+    PROPERTY_CLASS_NAME, PROPERTY_DRAG_PAYLOAD, PROPERTY_DROP_TARGET, PROPERTY_FOCUSABLE,
+    PROPERTY_LAYOUT_KEYS, PROPERTY_LAYOUT_ON_RESIZE, PROPERTY_RECEIVE_BUBBLED, PROPERTY_SCROLLABLE,
+    PROPERTY_TAB_INDEX,
+};
+use crate::registry::WidgetRegistry;
+use log::warn;
+use pushrod_widgets::caches::WidgetCache;
+use pushrod_widgets::properties::{
+    PROPERTY_BORDER_COLOR, PROPERTY_BORDER_WIDTH, PROPERTY_BOUNDS, PROPERTY_DISABLED,
+    PROPERTY_FONT_NAME, PROPERTY_FONT_SIZE, PROPERTY_FONT_STYLE, PROPERTY_GRID_COLOR,
+    PROPERTY_GRID_CONNECTED, PROPERTY_GRID_SPACING, PROPERTY_GROUP_BACKGROUND_COLOR,
+    PROPERTY_GROUP_ID, PROPERTY_HIDDEN, PROPERTY_IMAGE_FILENAME, PROPERTY_IMAGE_POSITION,
+    PROPERTY_IMAGE_SCALED, PROPERTY_MAIN_COLOR, PROPERTY_MENU_ITEM_ID, PROPERTY_ORIGIN,
+    PROPERTY_PROGRESS, PROPERTY_PROGRESS_COLOR, PROPERTY_TEXT, PROPERTY_TEXT_JUSTIFICATION,
+    PROPERTY_TOGGLED,
+};
+use pushrod_widgets::widget::Widget;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+/// This is the name used in a layout to refer to the top-level `Widget` (ID 0) as a parent.
+pub const ROOT_NAME: &str = "root";
+
+/// These are the properties that are saved when a `WidgetCache` is written as a layout.  Since
+/// `WidgetProperties` cannot be enumerated, each of these is checked on every `Widget`, along with
+/// the keys listed in its `PROPERTY_LAYOUT_KEYS`, if it was created from a layout.  Properties
+/// that only reflect the runtime state of a `Widget`, such as `PROPERTY_INVALIDATED`,
+/// `PROPERTY_NEEDS_LAYOUT` and `PROPERTY_FOCUSED`, are not saved.
+const SAVED_PROPERTIES: &[u32] = &[
+    PROPERTY_ORIGIN,
+    PROPERTY_BOUNDS,
+    PROPERTY_MAIN_COLOR,
+    PROPERTY_BORDER_COLOR,
+    PROPERTY_BORDER_WIDTH,
+    PROPERTY_TEXT,
+    PROPERTY_FONT_NAME,
+    PROPERTY_FONT_SIZE,
+    PROPERTY_FONT_STYLE,
+    PROPERTY_TEXT_JUSTIFICATION,
+    PROPERTY_HIDDEN,
+    PROPERTY_DISABLED,
+    PROPERTY_PROGRESS,
+    PROPERTY_PROGRESS_COLOR,
+    PROPERTY_GRID_COLOR,
+    PROPERTY_GRID_CONNECTED,
+    PROPERTY_GRID_SPACING,
+    PROPERTY_GROUP_ID,
+    PROPERTY_GROUP_BACKGROUND_COLOR,
+    PROPERTY_IMAGE_FILENAME,
+    PROPERTY_IMAGE_POSITION,
+    PROPERTY_IMAGE_SCALED,
+    PROPERTY_MENU_ITEM_ID,
+    PROPERTY_TOGGLED,
+    PROPERTY_FOCUSABLE,
+    PROPERTY_TAB_INDEX,
+    PROPERTY_LAYOUT_ON_RESIZE,
+    PROPERTY_DRAG_PAYLOAD,
+    PROPERTY_DROP_TARGET,
+    PROPERTY_SCROLLABLE,
+    PROPERTY_RECEIVE_BUBBLED,
+];
+
+/// This is the mapping of `Widget` names in a layout to the IDs that they were given when they
+/// were added to the `WidgetCache`.
+pub type LayoutIds = HashMap<String, u32>;
+
+/// This is the mapping of the IDs of `Widget`s from a previous layout to the IDs of the `Widget`s
+/// that replaced them when the layout was reloaded.
+pub type LayoutRemap = HashMap<u32, u32>;
+
+/// This is an error in a layout, along with the line and column (both starting at 1) at which it
+/// was found.  Errors found while writing a layout have no position, and a `line` and `column` of
+/// 0.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayoutError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            return write!(f, "{}", self.message);
+        }
+
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for LayoutError {}
+
+/// Creates a `Error::Layout` for the `line` and `column` specified.
+fn layout_error<S: Into<String>>(line: usize, column: usize, message: S) -> Error {
+    Error::Layout(LayoutError {
+        line,
+        column,
+        message: message.into(),
+    })
+}
+
+/// Returns the list of property keys stored in `PROPERTY_LAYOUT_KEYS` for a `LayoutWidget`.
+fn layout_keys(widget: &LayoutWidget) -> String {
+    widget
+        .properties
+        .iter()
+        .map(|(key, _)| key.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Checks that a `Widget` name can be written to a layout and read back, returning the reason if
+/// it cannot.
+fn validate_name(name: &str) -> std::result::Result<(), &'static str> {
+    if name.is_empty() {
+        Err("names cannot be empty")
+    } else if name.contains(char::is_whitespace) || name.contains(':') {
+        Err("names cannot contain whitespace or `:`")
+    } else if name.starts_with('#') {
+        Err("names cannot start with `#`")
+    } else if name == ROOT_NAME {
+        Err("`root` refers to the top-level widget")
+    } else {
+        Ok(())
+    }
+}
+
+/// This is the format of a property value, used to validate the values in a layout.  Properties
+/// that are not known to the loader are stored as-is.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PropertyFormat {
+    Point,
+    Color,
+    Value,
+    Text,
+}
+
+/// Returns the format of the property with the key specified.
+fn property_format(key: u32) -> PropertyFormat {
+    match key {
+        PROPERTY_ORIGIN | PROPERTY_BOUNDS => PropertyFormat::Point,
+
+        PROPERTY_MAIN_COLOR
+        | PROPERTY_BORDER_COLOR
+        | PROPERTY_GRID_COLOR
+        | PROPERTY_GROUP_BACKGROUND_COLOR
+        | PROPERTY_PROGRESS_COLOR => PropertyFormat::Color,
+
+        PROPERTY_BORDER_WIDTH
+        | PROPERTY_FONT_SIZE
+        | PROPERTY_FONT_STYLE
+        | PROPERTY_TEXT_JUSTIFICATION
+        | PROPERTY_PROGRESS
+        | PROPERTY_GRID_SPACING
+        | PROPERTY_GROUP_ID
+        | PROPERTY_IMAGE_POSITION
+        | PROPERTY_MENU_ITEM_ID => PropertyFormat::Value,
+
+        _ => PropertyFormat::Text,
+    }
+}
+
+/// Checks that `value` is in the correct format for the property with the key specified, returning
+/// a description of the expected format if it is not.
+fn validate_property(key: u32, value: &str) -> std::result::Result<(), &'static str> {
+    let fields: Vec<&str> = value.split_whitespace().collect();
+
+    match property_format(key) {
+        PropertyFormat::Point => {
+            if fields.len() == 2 && fields.iter().all(|f| f.parse::<u32>().is_ok()) {
+                Ok(())
+            } else {
+                Err("two unsigned numbers, such as `20 20`")
+            }
+        }
+
+        PropertyFormat::Color => {
+            if fields.len() == 4 && fields.iter().all(|f| f.parse::<u8>().is_ok()) {
+                Ok(())
+            } else {
+                Err("four color components from 0 to 255, such as `255 255 255 255`")
+            }
+        }
+
+        PropertyFormat::Value => {
+            if fields.len() == 1 && fields[0].parse::<i32>().is_ok() {
+                Ok(())
+            } else {
+                Err("a single number, such as `10`")
+            }
+        }
+
+        PropertyFormat::Text => Ok(()),
+    }
+}
+
+/// Returns the ID of the parent of a `Widget` described by a layout, from the IDs of the `Widget`s
+/// that have already been added.  Parents must be described before their children.
+fn parent_id_of(widget: &LayoutWidget, ids: &LayoutIds) -> u32 {
+    if widget.parent == ROOT_NAME {
+        0
+    } else {
+        ids[&widget.parent]
+    }
+}
+
+/// Creates a `Widget` described by a layout using the `registry`, setting its properties, its
+/// `PROPERTY_CLASS_NAME`, and its `PROPERTY_LAYOUT_KEYS`.  The class must be in the `registry`.
+fn create_widget(widget: &LayoutWidget, registry: &WidgetRegistry) -> Box<dyn Widget> {
+    let mut new_widget = registry.create(&widget.class).unwrap();
+
+    for (key, value) in widget.properties.iter() {
+        new_widget.properties().set(*key, value.clone());
+    }
+
+    new_widget
+        .properties()
+        .set(PROPERTY_CLASS_NAME, widget.class.clone())
+        .set(PROPERTY_LAYOUT_KEYS, layout_keys(widget));
+
+    new_widget
+}
+
+/// This is a single `Widget` described by a layout: its name, the name of its parent, its class
+/// name, and its properties, stored as the raw strings that are set on the `Widget`.
+#[derive(Clone, Debug)]
+pub struct LayoutWidget {
+    pub name: String,
+    pub parent: String,
+    pub class: String,
+    pub properties: Vec<(u32, String)>,
+    position: (usize, usize),
+    parent_position: (usize, usize),
+    class_position: (usize, usize),
+}
+
+/// This is a parsed layout resource (`.rsc`) file.  A layout is a list of named `Widget`s, each of
+/// which has a parent, a class, and a list of properties, keyed by their numeric property IDs:
+///
+/// ```text
+/// grid1:
+///   parent: root
+///   class: GridWidget
+///   properties:
+///     - 4: 20 20
+///     - 5: 280 440
+/// ```
+///
+/// Parents are referred to by name, and must be described before their children; `root` refers to
+/// the top-level `Widget`.  Property values are the raw strings stored by `WidgetProperties`, and
+/// extend to the end of the line, with `\n`, `\r` and `\\` used for line breaks and backslashes.
+/// Lines starting with `#` are comments.
+///
+/// A `LayoutDocument` can also be created from the `Widget`s in a `WidgetCache` using `from_cache`,
+/// and written out in the same format, which can be loaded again without losing any properties.
+#[derive(Clone, Debug, Default)]
+pub struct LayoutDocument {
+    pub widgets: Vec<LayoutWidget>,
+}
+
+/// This is an implementation of the `LayoutDocument`.
+impl LayoutDocument {
+    /// Reads and parses the layout in the file at `path`.  Returns `Error::Io` if the file cannot
+    /// be read, or `Error::Layout` if it is not a valid layout.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parses a layout from its `source` text.  Returns `Error::Layout` with the line and column
+    /// of the first error found if it is not a valid layout.
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut widgets: Vec<LayoutWidget> = Vec::new();
+        let mut names: HashSet<String> = HashSet::new();
+        let mut in_properties = false;
+
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            let trimmed = line.trim_start();
+
+            if trimmed.trim_end().is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            let column = line.len() - trimmed.len() + 1;
+
+            // Unindented lines start a new Widget.
+            if column == 1 {
+                let name = match trimmed.trim_end().strip_suffix(':') {
+                    Some(name) if !name.is_empty() && !name.contains(char::is_whitespace) => name,
+                    _ => {
+                        return Err(layout_error(
+                            line_number,
+                            column,
+                            "Expected a widget name followed by `:`",
+                        ))
+                    }
+                };
+
+                if name == ROOT_NAME || !names.insert(String::from(name)) {
+                    return Err(layout_error(
+                        line_number,
+                        column,
+                        format!("Widget name `{}` is already in use", name),
+                    ));
+                }
+
+                in_properties = false;
+                widgets.push(LayoutWidget {
+                    name: String::from(name),
+                    parent: String::from(ROOT_NAME),
+                    class: String::new(),
+                    properties: Vec::new(),
+                    position: (line_number, column),
+                    parent_position: (line_number, column),
+                    class_position: (line_number, column),
+                });
+
+                continue;
+            }
+
+            let widget = match widgets.last_mut() {
+                Some(widget) => widget,
+                None => {
+                    return Err(layout_error(
+                        line_number,
+                        column,
+                        "Expected a widget name followed by `:`",
+                    ))
+                }
+            };
+
+            // Property entries: "- <id>: <value>"
+            if let Some(entry) = trimmed.strip_prefix('-') {
+                if !in_properties {
+                    return Err(layout_error(
+                        line_number,
+                        column,
+                        "Property found outside of a `properties:` section",
+                    ));
+                }
+
+                let key_column = column + 1 + (entry.len() - entry.trim_start().len());
+                let entry = entry.trim_start();
+                let separator = match entry.find(':') {
+                    Some(separator) => separator,
+                    None => {
+                        return Err(layout_error(
+                            line_number,
+                            key_column,
+                            "Expected a property in the form `- <property id>: <value>`",
+                        ))
+                    }
+                };
+                let key = entry[..separator].trim().parse::<u32>().map_err(|_| {
+                    layout_error(
+                        line_number,
+                        key_column,
+                        format!("Invalid property id `{}`", entry[..separator].trim()),
+                    )
+                })?;
+                let raw_value = &entry[separator + 1..];
+                let (raw_value, value_column) = match raw_value.strip_prefix(' ') {
+                    Some(stripped) => (stripped, key_column + separator + 2),
+                    None => (raw_value, key_column + separator + 1),
+                };
+                let value = unescape_text(raw_value);
+
+                if let Err(expected) = validate_property(key, &value) {
+                    return Err(layout_error(
+                        line_number,
+                        value_column,
+                        format!(
+                            "Malformed value `{}` for property {}: expected {}",
+                            raw_value, key, expected
+                        ),
+                    ));
+                }
+
+                widget.properties.push((key, value));
+                continue;
+            }
+
+            // Widget attributes: "<key>: <value>"
+            let separator = match trimmed.find(':') {
+                Some(separator) => separator,
+                None => {
+                    return Err(layout_error(
+                        line_number,
+                        column,
+                        "Expected `parent:`, `class:` or `properties:`",
+                    ))
+                }
+            };
+            let key = trimmed[..separator].trim();
+            let raw_value = &trimmed[separator + 1..];
+            let value_column =
+                column + separator + 1 + (raw_value.len() - raw_value.trim_start().len());
+            let value = raw_value.trim();
+
+            match key {
+                "parent" => {
+                    widget.parent = String::from(value);
+                    widget.parent_position = (line_number, value_column);
+                    in_properties = false;
+                }
+
+                "class" => {
+                    widget.class = String::from(value);
+                    widget.class_position = (line_number, value_column);
+                    in_properties = false;
+                }
+
+                "properties" => {
+                    if !value.is_empty() {
+                        return Err(layout_error(
+                            line_number,
+                            value_column,
+                            "Properties must be listed on the lines following `properties:`",
+                        ));
+                    }
+
+                    in_properties = true;
+                }
+
+                _ => {
+                    return Err(layout_error(
+                        line_number,
+                        column,
+                        format!(
+                            "Unknown key `{}`: expected `parent`, `class` or `properties`",
+                            key
+                        ),
+                    ))
+                }
+            }
+        }
+
+        if let Some(widget) = widgets.iter().find(|w| w.class.is_empty()) {
+            return Err(layout_error(
+                widget.position.0,
+                widget.position.1,
+                format!("Widget `{}` does not have a `class`", widget.name),
+            ));
+        }
+
+        Ok(Self { widgets })
+    }
+
+    /// Adds the `Widget`s described by this layout to the `cache`, in the order in which they are
+    /// described, setting their properties.  Each `Widget` also has its class name stored in
+    /// `PROPERTY_CLASS_NAME`.  Returns the IDs that the `Widget`s were given, by name.
+    ///
+    /// The layout is checked before any `Widget`s are added, so if an unknown class or parent is
+    /// found, `Error::Layout` is returned, and the `cache` is left unchanged.
+    ///
+    /// Only the system `Widget`s can be used.  To use other `Widget` classes, use `apply_with`.
+    pub fn apply(&self, cache: &mut WidgetCache) -> Result<LayoutIds> {
+        self.apply_with(cache, &WidgetRegistry::default())
+    }
+
+    /// Adds the `Widget`s described by this layout to the `cache`, as with `apply`, creating them
+    /// using the classes in the `registry`.
+    pub fn apply_with(
+        &self,
+        cache: &mut WidgetCache,
+        registry: &WidgetRegistry,
+    ) -> Result<LayoutIds> {
+        self.validate(registry)?;
+
+        let mut ids = LayoutIds::new();
+
+        for widget in self.widgets.iter() {
+            let parent_id = parent_id_of(widget, &ids);
+            let widget_id = cache.add(
+                create_widget(widget, registry),
+                widget.name.clone(),
+                parent_id,
+            );
+
+            ids.insert(widget.name.clone(), widget_id);
+        }
+
+        Ok(ids)
+    }
+
+    /// Checks that every parent is described before its children, and that every class is in the
+    /// `registry`, so that the layout can be applied without failing part of the way through.
+    fn validate(&self, registry: &WidgetRegistry) -> Result<()> {
+        let mut declared: HashSet<&str> = HashSet::new();
+
+        for widget in self.widgets.iter() {
+            if widget.parent != ROOT_NAME && !declared.contains(widget.parent.as_str()) {
+                return Err(layout_error(
+                    widget.parent_position.0,
+                    widget.parent_position.1,
+                    format!(
+                        "Unknown parent `{}`: parents must be described before their children",
+                        widget.parent
+                    ),
+                ));
+            }
+
+            if !registry.contains(&widget.class) {
+                return Err(layout_error(
+                    widget.class_position.0,
+                    widget.class_position.1,
+                    format!("Unknown class `{}`", widget.class),
+                ));
+            }
+
+            declared.insert(&widget.name);
+        }
+
+        Ok(())
+    }
+}
+
+/// This is an implementation of the `LayoutDocument` reloading, used by `Engine::watch_layout`.
+impl LayoutDocument {
+    /// Indicates whether or not this layout describes the same `Widget`s as the `other` layout:
+    /// the same names, parents and classes, in the same order.  If so, the `other` layout can be
+    /// reloaded by updating the properties of the existing `Widget`s.
+    pub(crate) fn has_same_widgets(&self, other: &LayoutDocument) -> bool {
+        self.widgets.len() == other.widgets.len()
+            && self
+                .widgets
+                .iter()
+                .zip(other.widgets.iter())
+                .all(|(a, b)| a.name == b.name && a.parent == b.parent && a.class == b.class)
+    }
+
+    /// Updates the properties of the `Widget`s that were added to the `cache` from the `previous`
+    /// layout, whose IDs are in `ids`, to the properties in this layout.  Properties that were set
+    /// by the `previous` layout and are no longer set by this one are deleted.  Each `Widget` is
+    /// invalidated, so that it is redrawn.  The layouts must describe the same `Widget`s.
+    pub(crate) fn update(&self, previous: &LayoutDocument, ids: &LayoutIds, cache: &WidgetCache) {
+        for (widget, previous_widget) in self.widgets.iter().zip(previous.widgets.iter()) {
+            let mut cached_widget = cache.get(ids[&widget.name]);
+
+            for (key, _) in previous_widget.properties.iter() {
+                if !widget.properties.iter().any(|(k, _)| k == key) {
+                    cached_widget.properties().delete(*key);
+                }
+            }
+
+            for (key, value) in widget.properties.iter() {
+                cached_widget.properties().set(*key, value.clone());
+            }
+
+            cached_widget
+                .properties()
+                .set(PROPERTY_LAYOUT_KEYS, layout_keys(widget));
+            cached_widget.invalidate();
+        }
+    }
+
+    /// Replaces the `Widget`s that were added to the `cache` from a previous layout, whose IDs are
+    /// in `previous_ids`, with the `Widget`s described by this layout, reusing their IDs rather
+    /// than adding new ones.  A `Widget` keeps the ID of the previous `Widget` with the same name if
+    /// it has the same parent; otherwise it takes any other unused ID with the same parent, from
+    /// the previous `Widget`s or from `spare_ids`.  New IDs are only
+    /// added when none of these are left.  The IDs that are not reused are hidden, and stored in
+    /// `spare_ids` for later reloads.  Returns the IDs of the `Widget`s in this layout, by name.
+    ///
+    /// As with `apply_with`, the `cache` is left unchanged if the layout cannot be applied.
+    pub(crate) fn replace(
+        &self,
+        cache: &mut WidgetCache,
+        registry: &WidgetRegistry,
+        previous_ids: &LayoutIds,
+        spare_ids: &mut Vec<u32>,
+    ) -> Result<LayoutIds> {
+        self.validate(registry)?;
+
+        // First, find the Widgets that keep their previous IDs: those with the same name and the
+        // same parent.  Parents are described first, so their new IDs are already known.
+        let mut kept = LayoutIds::new();
+
+        for widget in self.widgets.iter() {
+            let parent_id = if widget.parent == ROOT_NAME {
+                Some(0)
+            } else {
+                kept.get(&widget.parent).copied()
+            };
+
+            if let (Some(widget_id), Some(parent_id)) = (previous_ids.get(&widget.name), parent_id)
+            {
+                if cache.get_parent_of(*widget_id) == parent_id {
+                    kept.insert(widget.name.clone(), *widget_id);
+                }
+            }
+        }
+
+        // Any other previous IDs can be reused by Widgets with the same parent.
+        let kept_ids: HashSet<u32> = kept.values().copied().collect();
+        let mut unused_ids: Vec<u32> = previous_ids
+            .values()
+            .copied()
+            .filter(|widget_id| !kept_ids.contains(widget_id))
+            .chain(spare_ids.drain(..))
+            .collect();
+        let mut ids = LayoutIds::new();
+
+        unused_ids.sort_unstable();
+
+        for widget in self.widgets.iter() {
+            let parent_id = parent_id_of(widget, &ids);
+            let new_widget = create_widget(widget, registry);
+            let reused_id = match kept.get(&widget.name) {
+                Some(widget_id) => Some(*widget_id),
+                None => unused_ids
+                    .iter()
+                    .position(|widget_id| cache.get_parent_of(*widget_id) == parent_id)
+                    .map(|position| unused_ids.remove(position)),
+            };
+
+            let widget_id = match reused_id {
+                Some(widget_id) => {
+                    let cached_widget = cache.get_mut(widget_id);
+
+                    *cached_widget = new_widget;
+                    cached_widget.invalidate();
+                    widget_id
+                }
+                None => cache.add(new_widget, widget.name.clone(), parent_id),
+            };
+
+            ids.insert(widget.name.clone(), widget_id);
+        }
+
+        for widget_id in unused_ids.iter() {
+            cache.set_hidden(*widget_id, true);
+        }
+
+        *spare_ids = unused_ids;
+
+        Ok(ids)
+    }
+}
+
+/// These are the options used when a `WidgetCache` is written as a layout.  They supply the names
+/// of the `Widget`s, the class names of any `Widget`s that were not created from a layout, and any
+/// application-specific properties that should be saved along with the standard properties.
+#[derive(Clone, Debug, Default)]
+pub struct SaveOptions {
+    names: HashMap<u32, String>,
+    classes: HashMap<u32, String>,
+    properties: Vec<u32>,
+}
+
+/// This is an implementation of the `SaveOptions` builder.
+impl SaveOptions {
+    /// Uses the names in `ids` for the `Widget`s that they refer to, such as the `LayoutIds`
+    /// returned when a layout was loaded.  `Widget`s without a name are named `widget` followed by
+    /// their ID.  Names must not be empty, `root`, start with `#`, or contain whitespace or `:`.
+    pub fn names(mut self, ids: &LayoutIds) -> Self {
+        for (name, widget_id) in ids.iter() {
+            self.names.insert(*widget_id, name.clone());
+        }
+
+        self
+    }
+
+    /// Sets the class name of the `Widget` with the ID `widget_id`.  This is only needed for
+    /// `Widget`s that were not created from a layout, as those have their class name stored in
+    /// `PROPERTY_CLASS_NAME`.
+    pub fn class(mut self, widget_id: u32, class_name: &str) -> Self {
+        self.classes.insert(widget_id, String::from(class_name));
+        self
+    }
+
+    /// Adds an application-specific property key to be saved for every `Widget` that has it set.
+    /// This is only needed for `Widget`s that were not created from a layout, as those have the
+    /// keys of the properties that they were loaded with stored in `PROPERTY_LAYOUT_KEYS`.
+    pub fn property(mut self, key: u32) -> Self {
+        self.properties.push(key);
+        self
+    }
+}
+
+/// This is an implementation of the `LayoutDocument` writer.
+impl LayoutDocument {
+    /// Creates a `LayoutDocument` describing the `Widget`s in the `cache`, other than the top-level
+    /// `Widget`.  The class of each `Widget` is taken from the `options`, or from its
+    /// `PROPERTY_CLASS_NAME`.  `Widget`s with no known class are skipped, along with their children,
+    /// and a warning is logged for each of them; this includes `Widget`s that were added by another
+    /// `Widget`'s `build_layout`, which are recreated when their parent is loaded.
+    ///
+    /// Returns `Error::Layout` if a `Widget` name cannot be written to a layout, or if two `Widget`s
+    /// would have the same name.
+    pub fn from_cache(cache: &WidgetCache, options: &SaveOptions) -> Result<Self> {
+        let mut keys: Vec<u32> = SAVED_PROPERTIES.to_vec();
+        let mut names: HashMap<u32, String> = HashMap::new();
+        let mut used_names: HashSet<String> = HashSet::new();
+        let mut widgets: Vec<LayoutWidget> = Vec::new();
+
+        keys.extend(options.properties.iter());
+        names.insert(0, String::from(ROOT_NAME));
+
+        for widget_id in 1..cache.size() {
+            let parent_id = cache.get_parent_of(widget_id);
+            let parent = match names.get(&parent_id) {
+                Some(parent) => parent.clone(),
+                None => {
+                    warn!(
+                        "Not saving widget {}: its parent {} was not saved",
+                        widget_id, parent_id
+                    );
+                    continue;
+                }
+            };
+            let mut widget = cache.get(widget_id);
+            let properties = widget.properties();
+            let class = match options.classes.get(&widget_id) {
+                Some(class) => class.clone(),
+                None => properties.get(PROPERTY_CLASS_NAME),
+            };
+
+            if class.is_empty() {
+                warn!(
+                    "Not saving widget {}: its class is unknown, and can be set with \
+                     `SaveOptions::class`",
+                    widget_id
+                );
+                continue;
+            }
+
+            let name = match options.names.get(&widget_id) {
+                Some(name) => name.clone(),
+                None => format!("widget{}", widget_id),
+            };
+
+            if let Err(reason) = validate_name(&name) {
+                return Err(layout_error(
+                    0,
+                    0,
+                    format!(
+                        "Invalid name `{}` for widget {}: {}",
+                        name, widget_id, reason
+                    ),
+                ));
+            }
+
+            if !used_names.insert(name.clone()) {
+                return Err(layout_error(
+                    0,
+                    0,
+                    format!(
+                        "Widget name `{}` is used by more than one widget, including widget {}",
+                        name, widget_id
+                    ),
+                ));
+            }
+
+            let mut widget_keys = keys.clone();
+
+            widget_keys.extend(
+                properties
+                    .get(PROPERTY_LAYOUT_KEYS)
+                    .split_whitespace()
+                    .filter_map(|key| key.parse::<u32>().ok()),
+            );
+            widget_keys.sort_unstable();
+            widget_keys.dedup();
+
+            let saved_properties = widget_keys
+                .iter()
+                .filter(|key| **key != PROPERTY_CLASS_NAME && **key != PROPERTY_LAYOUT_KEYS)
+                .map(|key| (*key, properties.get(*key)))
+                .filter(|(_, value)| !value.is_empty())
+                .collect();
+
+            names.insert(widget_id, name.clone());
+            widgets.push(LayoutWidget {
+                name,
+                parent,
+                class,
+                properties: saved_properties,
+                position: (0, 0),
+                parent_position: (0, 0),
+                class_position: (0, 0),
+            });
+        }
+
+        Ok(Self { widgets })
+    }
+
+    /// Writes the layout to the file at `path`, replacing it if it already exists.  Returns
+    /// `Error::Io` if the file cannot be written.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        std::fs::write(path, self.to_string())?;
+
+        Ok(())
+    }
+}
+
+/// Writes the layout in the `.rsc` format, with a blank line between `Widget`s.
+impl fmt::Display for LayoutDocument {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, widget) in self.widgets.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+
+            writeln!(f, "{}:", widget.name)?;
+            writeln!(f, "  parent: {}", widget.parent)?;
+            writeln!(f, "  class: {}", widget.class)?;
+            writeln!(f, "  properties:")?;
+
+            for (key, value) in widget.properties.iter() {
+                writeln!(f, "    - {}: {}", key, escape_text(value))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Loads the layout in the file at `path`, adding its `Widget`s to the `cache`.  Returns the IDs
+/// that the `Widget`s were given, by name.  This is typically called from
+/// `EventHandler::build_layout`:
+///
+/// ```rust,no_run
+/// use pushrod::layout::load_layout;
+/// # use pushrod_widgets::caches::WidgetCache;
+/// # fn build_layout(cache: &mut WidgetCache) {
+///
+/// let ids = load_layout("assets/examples/grid_widget.rsc", cache).unwrap();
+/// let grid1_id = ids["grid1"];
+/// # }
+/// ```
+pub fn load_layout<P: AsRef<Path>>(path: P, cache: &mut WidgetCache) -> Result<LayoutIds> {
+    LayoutDocument::load(path)?.apply(cache)
+}
+
+/// Loads the layout in the file at `path`, adding its `Widget`s to the `cache`, as with
+/// `load_layout`, creating them using the classes in the `registry`.
+pub fn load_layout_with<P: AsRef<Path>>(
+    path: P,
+    cache: &mut WidgetCache,
+    registry: &WidgetRegistry,
+) -> Result<LayoutIds> {
+    LayoutDocument::load(path)?.apply_with(cache, registry)
+}
+
+/// Writes the `Widget`s in the `cache` to the file at `path` as a layout, using the `options`
+/// to name them, and to identify their classes.  See `LayoutDocument::from_cache`.
+pub fn save_layout<P: AsRef<Path>>(
+    path: P,
+    cache: &WidgetCache,
+    options: &SaveOptions,
+) -> Result<()> {
+    LayoutDocument::from_cache(cache, options)?.save(path)
+}
+
+/// Declares a layout of `Widget`s in code, along with a struct that holds the IDs of the `Widget`s
+/// once they have been added to a `WidgetCache`.  Each `Widget` is given a name, which becomes a
+/// field of the struct, its class, which is the type of the `Widget`, and its parent, which is
+/// either `root` for the top-level `Widget`, or the name of a `Widget` declared before it; naming a
+/// parent that is declared later is a compile error.  As with layout files, a `Widget` cannot be
+/// named `root`.  Its properties are then set using the
+/// following forms:
+///
+/// - `origin(x, y)` and `bounds(w, h)` set the position and size of the `Widget`
+/// - `color(KEY, color)` sets a color property to an `SDL2` `Color`
+/// - `value(KEY, value)` sets a numeric property
+/// - `text(KEY, "text")` sets a text property
+/// - `flag(KEY)` sets a boolean property
+///
+/// The generated struct has a public `u32` field for each `Widget`, and a `build` function, which
+/// adds the `Widget`s to the `cache` in the order in which they are declared, and returns their
+/// IDs.  Any attributes given to the struct, such as `#[derive(Default)]`, are kept.  As with
+/// layouts loaded from a file, each `Widget` has its class name stored in `PROPERTY_CLASS_NAME`,
+/// and the keys of its properties in `PROPERTY_LAYOUT_KEYS`, so that it can be saved as a layout.
+///
+/// ```rust,no_run
+/// use pushrod_widgets::caches::WidgetCache;
+/// use pushrod_widgets::properties::{PROPERTY_BORDER_WIDTH, PROPERTY_MAIN_COLOR, PROPERTY_TEXT};
+/// use pushrod_widgets::system_widgets::base_widget::BaseWidget;
+/// use pushrod_widgets::system_widgets::button_widget::ButtonWidget;
+/// use sdl2::pixels::Color;
+///
+/// pushrod::layout! {
+///     #[derive(Clone, Copy, Debug, Default)]
+///     pub struct ExampleIds {
+///         panel: BaseWidget in root {
+///             origin(20, 20),
+///             bounds(200, 100),
+///             color(PROPERTY_MAIN_COLOR, Color::GREY),
+///             value(PROPERTY_BORDER_WIDTH, 1),
+///         },
+///         button: ButtonWidget in panel {
+///             origin(40, 40),
+///             bounds(120, 32),
+///             text(PROPERTY_TEXT, "Click Me"),
+///         },
+///     }
+/// }
+///
+/// # fn build_layout(cache: &mut WidgetCache) {
+/// let ids = ExampleIds::build(cache);
+/// # }
+/// ```
+///
+/// Since each parent must be declared before its children, this does not compile:
+///
+/// ```rust,compile_fail
+/// use pushrod_widgets::system_widgets::base_widget::BaseWidget;
+///
+/// pushrod::layout! {
+///     pub struct ForwardIds {
+///         child: BaseWidget in panel { origin(40, 40) },
+///         panel: BaseWidget in root { origin(20, 20) },
+///     }
+/// }
+/// ```
+///
+/// Nor does this, as `root` always refers to the top-level `Widget`:
+///
+/// ```rust,compile_fail
+/// use pushrod_widgets::system_widgets::base_widget::BaseWidget;
+///
+/// pushrod::layout! {
+///     pub struct RootIds {
+///         root: BaseWidget in root { origin(20, 20) },
+///         child: BaseWidget in root { origin(40, 40) },
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! layout {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $ids:ident {
+            $(
+                $name:ident : $class:ident in $parent:ident {
+                    $( $kind:ident ( $($args:tt)* ) ),* $(,)?
+                }
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $ids {
+            $( pub $name: u32, )*
+        }
+
+        impl $ids {
+            /// Adds the `Widget`s in this layout to the `cache`, returning their IDs.
+            pub fn build(cache: &mut $crate::pushrod_widgets::caches::WidgetCache) -> Self {
+                // Each ID is bound to a local named after its Widget, so that a parent can only be
+                // referred to once it has been added.
+                $(
+                    $crate::layout!(@name $name);
+
+                    let mut widget = $class::default();
+                    let properties = $crate::pushrod_widgets::widget::Widget::properties(&mut widget);
+                    let keys: Vec<String> = vec![
+                        $( $crate::layout!(@key $kind($($args)*)).to_string() ),*
+                    ];
+
+                    $( $crate::layout!(@property properties, $kind($($args)*)); )*
+
+                    properties
+                        .set(
+                            $crate::properties::PROPERTY_CLASS_NAME,
+                            String::from(stringify!($class)),
+                        )
+                        .set($crate::properties::PROPERTY_LAYOUT_KEYS, keys.join(" "));
+
+                    let $name = cache.add(
+                        Box::new(widget),
+                        String::from(stringify!($name)),
+                        $crate::layout!(@parent $parent),
+                    );
+                )*
+
+                Self { $( $name, )* }
+            }
+        }
+    };
+
+    (@name root) => {
+        compile_error!("A layout Widget cannot be named `root`, which refers to the top-level Widget");
+    };
+
+    (@name $name:ident) => {};
+
+    (@parent root) => {
+        0
+    };
+
+    (@parent $parent:ident) => {
+        $parent
+    };
+
+    (@key origin($($args:tt)*)) => {
+        $crate::pushrod_widgets::properties::PROPERTY_ORIGIN
+    };
+
+    (@key bounds($($args:tt)*)) => {
+        $crate::pushrod_widgets::properties::PROPERTY_BOUNDS
+    };
+
+    (@key $kind:ident($key:expr $(, $($args:tt)*)?)) => {
+        $key
+    };
+
+    (@property $properties:ident, origin($x:expr, $y:expr $(,)?)) => {
+        $properties.set_origin($x, $y);
+    };
+
+    (@property $properties:ident, bounds($w:expr, $h:expr $(,)?)) => {
+        $properties.set_bounds($w, $h);
+    };
+
+    (@property $properties:ident, color($key:expr, $color:expr $(,)?)) => {
+        $properties.set_color($key, $color);
+    };
+
+    (@property $properties:ident, value($key:expr, $value:expr $(,)?)) => {
+        $properties.set_value($key, $value);
+    };
+
+    (@property $properties:ident, text($key:expr, $text:expr $(,)?)) => {
+        $properties.set($key, String::from($text));
+    };
+
+    (@property $properties:ident, flag($key:expr $(,)?)) => {
+        $properties.set_bool($key);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pushrod_widgets::system_widgets::box_widget::BoxWidget;
+
+    /// Parses `source`, expecting it to fail, and returns the `LayoutError`.
+    fn parse_error(source: &str) -> LayoutError {
+        match LayoutDocument::parse(source) {
+            Err(Error::Layout(error)) => error,
+            Err(error) => panic!("Expected a layout error, got: {}", error),
+            Ok(_) => panic!("Expected a layout error for:\n{}", source),
+        }
+    }
+
+    /// Parses and applies `source` to an empty `WidgetCache`, expecting it to fail, and returns
+    /// the `LayoutError`.
+    fn apply_error(source: &str) -> LayoutError {
+        let mut cache = WidgetCache::new(400, 400);
+
+        match LayoutDocument::parse(source).unwrap().apply(&mut cache) {
+            Err(Error::Layout(error)) => error,
+            Err(error) => panic!("Expected a layout error, got: {}", error),
+            Ok(_) => panic!("Expected a layout error for:\n{}", source),
+        }
+    }
+
+    #[test]
+    fn test_parse_grid_widget_example() {
+        let document = LayoutDocument::load("assets/examples/grid_widget.rsc").unwrap();
+
+        assert_eq!(document.widgets.len(), 2);
+        assert_eq!(document.widgets[0].name, "grid1");
+        assert_eq!(document.widgets[0].parent, ROOT_NAME);
+        assert_eq!(document.widgets[0].class, "GridWidget");
+        assert_eq!(document.widgets[0].properties.len(), 8);
+        assert_eq!(
+            document.widgets[0].properties[0],
+            (PROPERTY_ORIGIN, String::from("20 20"))
+        );
+        assert_eq!(document.widgets[1].name, "grid2");
+        assert_eq!(document.widgets[1].properties.len(), 7);
+        assert_eq!(
+            document.widgets[1].properties[0],
+            (PROPERTY_ORIGIN, String::from("320 20"))
+        );
+    }
+
+    #[test]
+    fn test_malformed_value_position() {
+        let source = format!(
+            "box1:\n  parent: root\n  class: BoxWidget\n  properties:\n    - {}: 20\n",
+            PROPERTY_ORIGIN
+        );
+        let error = parse_error(&source);
+        let value_column = 9 + PROPERTY_ORIGIN.to_string().len();
+
+        assert_eq!((error.line, error.column), (5, value_column));
+        assert!(error.message.starts_with("Malformed value `20`"));
+    }
+
+    #[test]
+    fn test_unknown_class_position() {
+        let error = apply_error("box1:\n  parent: root\n  class: NoSuchWidget\n");
+
+        assert_eq!((error.line, error.column), (3, 10));
+        assert_eq!(error.message, "Unknown class `NoSuchWidget`");
+    }
+
+    #[test]
+    fn test_unknown_parent_position() {
+        let error = apply_error("box1:\n  parent: box2\n  class: BoxWidget\n");
+
+        assert_eq!((error.line, error.column), (2, 11));
+        assert!(error.message.starts_with("Unknown parent `box2`"));
+    }
+
+    #[test]
+    fn test_missing_class_position() {
+        let error =
+            parse_error("box1:\n  parent: root\n\nbox2:\n  parent: root\n  class: BoxWidget\n");
+
+        assert_eq!((error.line, error.column), (1, 1));
+        assert_eq!(error.message, "Widget `box1` does not have a `class`");
+    }
+
+    #[test]
+    fn test_duplicate_name_position() {
+        let error = parse_error(
+            "box1:\n  parent: root\n  class: BoxWidget\n\nbox1:\n  parent: root\n  class: BoxWidget\n",
+        );
+
+        assert_eq!((error.line, error.column), (5, 1));
+        assert_eq!(error.message, "Widget name `box1` is already in use");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut document = LayoutDocument::load("assets/examples/grid_widget.rsc").unwrap();
+
+        document.widgets[0]
+            .properties
+            .push((PROPERTY_TEXT, String::from("line 1\nline 2\r\nC:\\path\\")));
+        document.widgets[1]
+            .properties
+            .push((PROPERTY_FONT_NAME, String::from("  padded  ")));
+        document.widgets[1]
+            .properties
+            .push((999, String::from("x")));
+
+        let reparsed = LayoutDocument::parse(&document.to_string()).unwrap();
+
+        assert_eq!(reparsed.widgets.len(), document.widgets.len());
+
+        for (widget, reparsed_widget) in document.widgets.iter().zip(reparsed.widgets.iter()) {
+            assert_eq!(reparsed_widget.name, widget.name);
+            assert_eq!(reparsed_widget.parent, widget.parent);
+            assert_eq!(reparsed_widget.class, widget.class);
+            assert_eq!(reparsed_widget.properties, widget.properties);
+        }
+    }
+
+    #[test]
+    fn test_save_keeps_loaded_properties() {
+        let mut cache = WidgetCache::new(400, 400);
+        let source = format!(
+            "box1:\n  parent: root\n  class: BoxWidget\n  properties:\n    - {}: 20 20\n    - 999: x\n\n\
+             box2:\n  parent: box1\n  class: TextWidget\n",
+            PROPERTY_ORIGIN
+        );
+        let document = LayoutDocument::parse(&source).unwrap();
+        let ids = document.apply(&mut cache).unwrap();
+        let saved =
+            LayoutDocument::from_cache(&cache, &SaveOptions::default().names(&ids)).unwrap();
+
+        assert_eq!(saved.widgets.len(), 2);
+
+        for (widget, saved_widget) in document.widgets.iter().zip(saved.widgets.iter()) {
+            assert_eq!(saved_widget.name, widget.name);
+            assert_eq!(saved_widget.parent, widget.parent);
+            assert_eq!(saved_widget.class, widget.class);
+            assert_eq!(saved_widget.properties, widget.properties);
+        }
+    }
+
+    #[test]
+    fn test_save_rejects_invalid_names() {
+        let mut cache = WidgetCache::new(400, 400);
+        let ids = LayoutDocument::parse("box1:\n  parent: root\n  class: BoxWidget\n")
+            .unwrap()
+            .apply(&mut cache)
+            .unwrap();
+
+        for name in &["", "has space", "has:colon", "#comment", ROOT_NAME] {
+            let mut names = LayoutIds::new();
+
+            names.insert(String::from(*name), ids["box1"]);
+            assert!(
+                LayoutDocument::from_cache(&cache, &SaveOptions::default().names(&names)).is_err()
+            );
+        }
+
+        // A name that collides with the generated name of an unnamed Widget is rejected as well.
+        let mut names = LayoutIds::new();
+        let box2_id = cache.add(Box::new(BoxWidget::default()), String::from("box2"), 0);
+        let options = SaveOptions::default().class(box2_id, "BoxWidget");
+
+        names.insert(format!("widget{}", box2_id), ids["box1"]);
+        assert!(LayoutDocument::from_cache(&cache, &options.names(&names)).is_err());
+    }
+
+    #[test]
+    fn test_replace_reuses_ids() {
+        let registry = WidgetRegistry::default();
+        let mut cache = WidgetCache::new(400, 400);
+        let first = LayoutDocument::parse(
+            "a:\n  parent: root\n  class: BoxWidget\n\
+             b:\n  parent: root\n  class: BoxWidget\n\
+             c:\n  parent: a\n  class: BoxWidget\n",
+        )
+        .unwrap();
+        let ids = first.apply_with(&mut cache, &registry).unwrap();
+        let size = cache.size();
+        let mut spare_ids = Vec::new();
+
+        // `c` moves to the root, taking the ID of `b`, which was removed, and `d` takes the ID
+        // that `c` had under `a`.
+        let second = LayoutDocument::parse(
+            "a:\n  parent: root\n  class: BoxWidget\n\
+             c:\n  parent: root\n  class: BoxWidget\n\
+             d:\n  parent: a\n  class: BoxWidget\n",
+        )
+        .unwrap();
+        let second_ids = second
+            .replace(&mut cache, &registry, &ids, &mut spare_ids)
+            .unwrap();
+
+        assert_eq!(cache.size(), size);
+        assert_eq!(second_ids["a"], ids["a"]);
+        assert_eq!(second_ids["c"], ids["b"]);
+        assert_eq!(second_ids["d"], ids["c"]);
+        assert_eq!(cache.get_parent_of(second_ids["d"]), second_ids["a"]);
+        assert!(spare_ids.is_empty());
+
+        // Removing `d` leaves its ID spare and hidden, and adding it back reuses it.
+        let third = LayoutDocument::parse(
+            "a:\n  parent: root\n  class: BoxWidget\n\
+             c:\n  parent: root\n  class: BoxWidget\n",
+        )
+        .unwrap();
+        let third_ids = third
+            .replace(&mut cache, &registry, &second_ids, &mut spare_ids)
+            .unwrap();
+
+        assert_eq!(spare_ids, vec![second_ids["d"]]);
+        assert!(cache
+            .get(second_ids["d"])
+            .properties()
+            .get_bool(PROPERTY_HIDDEN));
+
+        let fourth_ids = second
+            .replace(&mut cache, &registry, &third_ids, &mut spare_ids)
+            .unwrap();
+
+        assert_eq!(cache.size(), size);
+        assert_eq!(fourth_ids, second_ids);
+        assert!(spare_ids.is_empty());
+        assert!(!cache
+            .get(fourth_ids["d"])
+            .properties()
+            .get_bool(PROPERTY_HIDDEN));
+    }
+}
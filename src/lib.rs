@@ -14,8 +14,75 @@
 
 //! Pushrod is a GUI library written in Rust utilizing the [SDL2](https://www.github.com/Rust-SDL2/rust-sdl2)
 //! library for handling input and graphic events.
+//!
+//! Diagnostic output, such as event dispatch, layout building and redraws, is emitted through the
+//! [log](https://docs.rs/log) facade.  Nothing is printed unless the application installs a logger.
 
 /// This is the main event handler for the `Pushrod` library.  It contains the `Event` handling
 /// main loop, an `EventHandler` trait for interpreting events that are generated by the
 /// main loop, and an enumeration that stores the `PushrodEvent`s.
 pub mod engine;
+
+/// This is the configuration for an `Engine`, which is created using a builder pattern, and
+/// controls its frame rate, renderer, and input handling.
+pub mod config;
+
+/// These are the sources of time used by the `Engine`: a monotonic clock by default, and a manually
+/// advanced clock for deterministic testing.
+pub mod clock;
+
+/// This is the timer service, which delivers `TimerFired` events through the `Engine` after a
+/// delay, either once, or repeatedly.
+pub mod timer;
+
+/// This is the animation service, which animates `Widget` properties over time using easing
+/// curves, advanced on every frame by the `Engine`.
+pub mod animation;
+
+/// These are the events that are generated by the `Engine` that are not part of the
+/// `PushrodEvent` set, such as keyboard and text input events.
+pub mod event;
+
+/// These are the property keys that are used by the `Engine` to control how `Widget`s interact
+/// with it, such as whether or not a `Widget` can receive the keyboard focus.
+pub mod properties;
+
+/// This is the policy that controls how events generated by `Widget`s are redistributed to other
+/// `Widget`s, such as deselecting the other radio buttons in a group.
+pub mod rebroadcast;
+
+/// This is the input recorder, which writes the input dispatched by an `Engine` to a file, so that
+/// it can be played back later in place of input from `SDL2` to reproduce a session.
+pub mod recorder;
+
+/// This is the registry of `Widget` classes, which creates `Widget`s from their class names, so that
+/// system and application-defined `Widget`s can be used in layouts.
+pub mod registry;
+
+/// This is the layout loader, which reads `.rsc` layout resource files, and adds the `Widget`s
+/// that they describe to a `WidgetCache`.  It can also write a `WidgetCache` back out as a layout.
+/// Layouts can also be declared in code using the `layout!` macro.
+pub mod layout;
+
+/// This is a run loop for applications that have more than one window.  It shares a single
+/// `SDL2` event pump between multiple `Engine`s, routing each event to the appropriate window.
+pub mod application;
+
+/// This is a headless runner for the `Engine`, which renders into an offscreen software surface
+/// instead of a `Window`.  It allows frames to be stepped, synthetic input to be fed, and pixels to
+/// be read back, so that applications can be tested without a display.
+pub mod headless;
+
+/// These are the errors that can occur while creating or running an `Engine`.
+pub mod error;
+
+// This is the escaping of text that is written to a single line of a file, shared by the layout
+// writer and the input recorder.
+mod escape;
+
+pub use error::{Error, Result};
+
+// This is used by the `layout!` macro, so that applications do not need to name the
+// `pushrod_widgets` crate themselves for the macro to expand.
+#[doc(hidden)]
+pub use pushrod_widgets;
@@ -0,0 +1,76 @@
+// Pushrod Properties
+// Engine Property Constants
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// Indicates that a `Widget` can receive the keyboard focus, either by being clicked, or by
+/// being reached through `Tab`/`Shift-Tab` focus traversal.
+pub const PROPERTY_FOCUSABLE: u32 = 1000;
+
+/// Explicit focus traversal order of a `Widget`.  `Widget`s with a tab index are visited first,
+/// in ascending order, followed by the remaining focusable `Widget`s in the order in which they
+/// were added to the `WidgetCache`.  Setting this also makes the `Widget` focusable.
+pub const PROPERTY_TAB_INDEX: u32 = 1001;
+
+/// Set by the `Engine` on the `Widget` that currently has the keyboard focus, so that it can
+/// draw itself accordingly.  This should not be set directly; use `Engine::set_focused_widget`.
+pub const PROPERTY_FOCUSED: u32 = 1002;
+
+/// Indicates that a `Widget`'s layout should be rebuilt whenever the `Window` is resized.  When
+/// the size changes, the `Engine` sets `PROPERTY_NEEDS_LAYOUT` on the `Widget`, so that its
+/// `build_layout` function is called again.  The IDs of the `Widget`s from its previous layout
+/// are reused for the new ones, and any that are left over are hidden.  This is done at most once
+/// per frame, however many times the size changes in that frame.
+///
+/// This is the only way a `Widget` learns of a resize: the `WindowResized` event is only sent to
+/// the `EventHandler`, which must update any other `Widget`s that depend on the size of the
+/// `Window`.
+pub const PROPERTY_LAYOUT_ON_RESIZE: u32 = 1003;
+
+/// Indicates that a `Widget` is animating, and needs a `DrawFrame` event on every frame.  This is
+/// only used when the `Engine` runs in `LoopMode::EventDriven`, where frames are otherwise only
+/// drawn in response to events.  The `Widget` should clear this when its animation ends.
+pub const PROPERTY_ANIMATING: u32 = 1004;
+
+/// The drag-and-drop payload of a `Widget`.  If this is set when the mouse is dragged beyond the
+/// drag threshold after being pressed over the `Widget`, a drag is started, carrying this value.
+/// `Widget`s without a payload cannot be dragged.
+pub const PROPERTY_DRAG_PAYLOAD: u32 = 1005;
+
+/// Indicates that a `Widget` is a drop target, which receives `DragOver`, `DragLeave` and `Dropped`
+/// events while a drag is in progress.  Whether or not a drop is accepted is decided by the
+/// `EventHandler`'s `accept_drop` callback.
+pub const PROPERTY_DROP_TARGET: u32 = 1006;
+
+/// Indicates that a `Widget` can be scrolled.  A mouse wheel scroll is offered to the
+/// `EventHandler`'s `mouse_scrolled` callback for the `Widget` under the pointer, and then for each
+/// of its parents that has this property set, until the callback consumes it by returning `true`.
+pub const PROPERTY_SCROLLABLE: u32 = 1007;
+
+/// Indicates that a `Widget` receives pointer input (`MouseMoved` and `MouseButton` events) that
+/// bubbles up from its children, so that composite `Widget`s can react to interactions inside them.
+/// The bubbled event is unchanged, so its `widget_id` is that of the child that received it first.
+pub const PROPERTY_RECEIVE_BUBBLED: u32 = 1008;
+
+/// Set by a `Widget` while handling an event, to stop the event from bubbling up to its parents.
+/// The `Engine` clears this after each event is delivered.
+pub const PROPERTY_EVENT_CONSUMED: u32 = 1009;
+
+/// The class name of a `Widget`, such as `GridWidget`.  This is set on each `Widget` that is
+/// created from a layout, so that the class can be identified when the layout is saved.
+pub const PROPERTY_CLASS_NAME: u32 = 1010;
+
+/// The keys of the properties that a `Widget` was given by a layout, as a space-separated list.
+/// This is set on each `Widget` that is created from a layout, so that all of its properties,
+/// including application-specific ones, are written when the layout is saved.
+pub const PROPERTY_LAYOUT_KEYS: u32 = 1011;
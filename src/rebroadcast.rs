@@ -0,0 +1,344 @@
+// Pushrod Rebroadcast
+// Widget Event Rebroadcast Policy
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use log::warn;
+use pushrod_widgets::caches::WidgetCache;
+use pushrod_widgets::event::PushrodEvent;
+use pushrod_widgets::properties::PROPERTY_GROUP_ID;
+use std::mem::{discriminant, Discriminant};
+
+/// This is the set of `Widget`s to which an event generated by a `Widget` is redistributed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RebroadcastTarget {
+    /// All other `Widget`s with the same `PROPERTY_GROUP_ID` as the `Widget` that generated the
+    /// event.  If that `Widget` has no group ID, the event is not redistributed.
+    Group,
+
+    /// All other `Widget`s with the same parent as the `Widget` that generated the event.
+    Siblings,
+
+    /// Every `Widget` in the `WidgetCache`, including the `Widget` that generated the event.
+    All,
+}
+
+/// This is a single rule in a `RebroadcastPolicy`, which redistributes the events that it matches
+/// to its target.
+struct RebroadcastRule {
+    matches: Box<dyn Fn(&PushrodEvent) -> bool>,
+    target: RebroadcastTarget,
+}
+
+/// This is the policy that controls how the `Engine` redistributes the events that `Widget`s
+/// generate in response to other events.  After an event generated by a `Widget` has been sent to
+/// the `EventHandler`, it is checked against each rule in turn, and sent to the `Widget`s targeted
+/// by the first rule that matches it.  Any events that those `Widget`s generate in turn are
+/// redistributed the same way.
+///
+/// To prevent loops, each `Widget` receives a given kind of event at most once for each original
+/// event, and redistribution stops after `max_depth` levels.
+///
+/// The default policy redistributes `WidgetRadioSelected` events to all `Widget`s, so that the
+/// other radio buttons in the group are deselected.  Additional rules can be added for custom
+/// `Widget`s, and the policy given to the `Engine` using `Engine::set_rebroadcast_policy`:
+///
+/// ```rust,no_run
+/// use pushrod::rebroadcast::{RebroadcastPolicy, RebroadcastTarget};
+/// use pushrod_widgets::event::PushrodEvent;
+///
+/// let policy = RebroadcastPolicy::default().rule(
+///     |event| matches!(event, PushrodEvent::WidgetSelected { .. }),
+///     RebroadcastTarget::Group,
+/// );
+/// ```
+pub struct RebroadcastPolicy {
+    rules: Vec<RebroadcastRule>,
+    max_depth: u32,
+}
+
+/// The default policy redistributes `WidgetRadioSelected` events to all `Widget`s, with a maximum
+/// depth of 8.
+impl Default for RebroadcastPolicy {
+    fn default() -> Self {
+        Self::empty().rule(
+            |event| matches!(event, PushrodEvent::WidgetRadioSelected { .. }),
+            RebroadcastTarget::All,
+        )
+    }
+}
+
+/// This is an implementation of the `RebroadcastPolicy` builder.
+impl RebroadcastPolicy {
+    /// Creates a policy without any rules, so that no events are redistributed.
+    pub fn empty() -> Self {
+        Self {
+            rules: Vec::new(),
+            max_depth: 8,
+        }
+    }
+
+    /// Adds a rule that redistributes the events for which `matches` returns `true` to `target`.
+    /// Rules are checked in the order in which they were added.
+    pub fn rule<F>(mut self, matches: F, target: RebroadcastTarget) -> Self
+    where
+        F: Fn(&PushrodEvent) -> bool + 'static,
+    {
+        self.rules.push(RebroadcastRule {
+            matches: Box::new(matches),
+            target,
+        });
+        self
+    }
+
+    /// Sets the maximum number of levels of redistribution that are performed for a single event.
+    pub fn max_depth(mut self, max_depth: u32) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Returns the target of the first rule that matches the `event`, if any.
+    pub(crate) fn target_for(&self, event: &PushrodEvent) -> Option<RebroadcastTarget> {
+        self.rules
+            .iter()
+            .find(|rule| (rule.matches)(event))
+            .map(|rule| rule.target)
+    }
+
+    /// Redistributes an event generated by `source_widget_id` to the `Widget`s in the `cache`
+    /// targeted by this policy, if any of its rules match.  `deliver` sends an event to a `Widget`,
+    /// returning the event that the `Widget` generated in response, if any, which is redistributed
+    /// in turn, one level deeper.
+    pub(crate) fn rebroadcast<F>(
+        &self,
+        cache: &mut WidgetCache,
+        source_widget_id: u32,
+        event: PushrodEvent,
+        deliver: &mut F,
+    ) where
+        F: FnMut(&mut WidgetCache, u32, PushrodEvent) -> Option<PushrodEvent>,
+    {
+        self.rebroadcast_from(cache, source_widget_id, event, 0, &mut Vec::new(), deliver);
+    }
+
+    /// Redistributes an event at the given `depth`.  `delivered` records the kind of event sent to
+    /// each `Widget`, so that no `Widget` receives the same kind of event twice, which would
+    /// otherwise loop forever.
+    fn rebroadcast_from<F>(
+        &self,
+        cache: &mut WidgetCache,
+        source_widget_id: u32,
+        event: PushrodEvent,
+        depth: u32,
+        delivered: &mut Vec<(Discriminant<PushrodEvent>, u32)>,
+        deliver: &mut F,
+    ) where
+        F: FnMut(&mut WidgetCache, u32, PushrodEvent) -> Option<PushrodEvent>,
+    {
+        let target = match self.target_for(&event) {
+            Some(target) => target,
+            None => return,
+        };
+
+        if depth >= self.max_depth {
+            warn!("Rebroadcast depth limit reached: {:?}", event);
+
+            return;
+        }
+
+        for widget_id in rebroadcast_targets(cache, source_widget_id, target) {
+            let key = (discriminant(&event), widget_id);
+
+            if delivered.contains(&key) {
+                continue;
+            }
+
+            delivered.push(key);
+
+            if let Some(x) = deliver(cache, widget_id, event.clone()) {
+                self.rebroadcast_from(cache, widget_id, x, depth + 1, delivered, deliver);
+            }
+        }
+    }
+}
+
+/// Returns the IDs of the `Widget`s in the `cache` targeted when an event generated by
+/// `source_widget_id` is redistributed to `target`.
+fn rebroadcast_targets(
+    cache: &WidgetCache,
+    source_widget_id: u32,
+    target: RebroadcastTarget,
+) -> Vec<u32> {
+    let widget_count = cache.size();
+
+    match target {
+        RebroadcastTarget::All => (0..widget_count).collect(),
+
+        RebroadcastTarget::Siblings => {
+            let parent_id = cache.get_parent_of(source_widget_id);
+
+            (1..widget_count)
+                .filter(|i| *i != source_widget_id && cache.get_parent_of(*i) == parent_id)
+                .collect()
+        }
+
+        RebroadcastTarget::Group => {
+            let group_id = cache
+                .get(source_widget_id)
+                .properties()
+                .get(PROPERTY_GROUP_ID);
+
+            if group_id.is_empty() {
+                return Vec::new();
+            }
+
+            (0..widget_count)
+                .filter(|i| {
+                    *i != source_widget_id
+                        && cache.get(*i).properties().get(PROPERTY_GROUP_ID) == group_id
+                })
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pushrod_widgets::system_widgets::box_widget::BoxWidget;
+    use pushrod_widgets::widget::Widget;
+
+    /// Adds a `BoxWidget` to the `cache` under `parent_id`, with the `group_id`, if specified.
+    fn add_widget(cache: &mut WidgetCache, parent_id: u32, group_id: Option<&str>) -> u32 {
+        let mut widget = BoxWidget::default();
+
+        if let Some(group_id) = group_id {
+            widget
+                .properties()
+                .set(PROPERTY_GROUP_ID, String::from(group_id));
+        }
+
+        let widget_id = cache.size();
+
+        cache.add(Box::new(widget), format!("widget{}", widget_id), parent_id)
+    }
+
+    /// Creates a policy that redistributes `WidgetMouseEntered` events to `target`.
+    fn entered_policy(target: RebroadcastTarget) -> RebroadcastPolicy {
+        RebroadcastPolicy::empty().rule(
+            |event| matches!(event, PushrodEvent::WidgetMouseEntered { .. }),
+            target,
+        )
+    }
+
+    /// Rebroadcasts a `WidgetMouseEntered` event from `source_widget_id`, where every `Widget` that
+    /// receives it echoes it back as its own.  Returns the IDs of the `Widget`s that received it,
+    /// in order.
+    fn echo(
+        policy: &RebroadcastPolicy,
+        cache: &mut WidgetCache,
+        source_widget_id: u32,
+    ) -> Vec<u32> {
+        let mut received: Vec<u32> = Vec::new();
+
+        policy.rebroadcast(
+            cache,
+            source_widget_id,
+            PushrodEvent::WidgetMouseEntered {
+                widget_id: source_widget_id,
+            },
+            &mut |_, widget_id, _| {
+                received.push(widget_id);
+
+                Some(PushrodEvent::WidgetMouseEntered { widget_id })
+            },
+        );
+
+        received
+    }
+
+    #[test]
+    fn test_targets() {
+        let mut cache = WidgetCache::new(400, 400);
+        let a = add_widget(&mut cache, 0, Some("1"));
+        let b = add_widget(&mut cache, 0, Some("1"));
+        let c = add_widget(&mut cache, 0, None);
+        let d = add_widget(&mut cache, a, Some("1"));
+        let e = add_widget(&mut cache, a, Some("2"));
+
+        assert_eq!(
+            rebroadcast_targets(&cache, a, RebroadcastTarget::Siblings),
+            vec![b, c]
+        );
+        assert_eq!(
+            rebroadcast_targets(&cache, d, RebroadcastTarget::Siblings),
+            vec![e]
+        );
+        assert_eq!(
+            rebroadcast_targets(&cache, a, RebroadcastTarget::Group),
+            vec![b, d]
+        );
+        assert!(rebroadcast_targets(&cache, c, RebroadcastTarget::Group).is_empty());
+        assert_eq!(
+            rebroadcast_targets(&cache, a, RebroadcastTarget::All),
+            vec![0, a, b, c, d, e]
+        );
+    }
+
+    #[test]
+    fn test_echo_stops() {
+        let mut cache = WidgetCache::new(400, 400);
+        let a = add_widget(&mut cache, 0, None);
+        let b = add_widget(&mut cache, 0, None);
+
+        // `b` echoes back to `a`, whose echo is not sent to `b` again.
+        assert_eq!(
+            echo(&entered_policy(RebroadcastTarget::Siblings), &mut cache, a),
+            vec![b, a]
+        );
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let mut cache = WidgetCache::new(400, 400);
+        let a = add_widget(&mut cache, 0, None);
+        let b = add_widget(&mut cache, 0, None);
+        let policy = entered_policy(RebroadcastTarget::Siblings);
+
+        assert_eq!(echo(&policy.max_depth(1), &mut cache, a), vec![b]);
+
+        let policy = entered_policy(RebroadcastTarget::Siblings).max_depth(0);
+
+        assert!(echo(&policy, &mut cache, a).is_empty());
+    }
+
+    #[test]
+    fn test_unmatched_event() {
+        let mut cache = WidgetCache::new(400, 400);
+        let a = add_widget(&mut cache, 0, None);
+        let mut received = 0;
+
+        add_widget(&mut cache, 0, None);
+        entered_policy(RebroadcastTarget::Siblings).rebroadcast(
+            &mut cache,
+            a,
+            PushrodEvent::WidgetMouseExited { widget_id: a },
+            &mut |_, _, _| {
+                received += 1;
+                None
+            },
+        );
+
+        assert_eq!(received, 0);
+    }
+}
@@ -0,0 +1,425 @@
+// Pushrod Input Recorder
+// Input Recording and Replay
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::escape::{escape_text, unescape_text};
+use sdl2::keyboard::{Keycode, Mod, Scancode};
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Error, ErrorKind, Write};
+use std::path::Path;
+
+/// This is the header line that starts every input recording file.
+const RECORDING_HEADER: &str = "# pushrod input recording v1";
+
+/// This is a single piece of input, translated from `SDL2`, before it is dispatched by the
+/// `Engine`.  This is what gets recorded and replayed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InputEvent {
+    /// The mouse moved to the absolute `x` and `y` coordinates of the `Window`.
+    MouseMoved { x: u32, y: u32 },
+
+    /// A mouse button was pressed (`state` = `true`) or released (`state` = `false`).
+    MouseButton { button: u32, state: bool },
+
+    /// A key was pressed (`state` = `true`) or released (`state` = `false`).
+    Key {
+        keycode: Option<Keycode>,
+        scancode: Option<Scancode>,
+        keymod: Mod,
+        repeat: bool,
+        state: bool,
+    },
+
+    /// Text was entered.
+    TextInput { text: String },
+
+    /// The mouse wheel was scrolled by `x` and `y`, normalized so that positive values scroll
+    /// right and away from the user, regardless of the platform's scroll direction.
+    MouseWheel { x: i32, y: i32 },
+}
+
+/// Converts a `bool` to its recorded form.
+fn bool_to_field(value: bool) -> &'static str {
+    if value {
+        "1"
+    } else {
+        "0"
+    }
+}
+
+/// Converts an optional key or scan code to its recorded form, where `-` indicates `None`.
+fn code_to_field(code: Option<i32>) -> String {
+    match code {
+        Some(code) => format!("{}", code),
+        None => String::from("-"),
+    }
+}
+
+/// This is an implementation of the `InputEvent`, which converts it to and from its recorded form.
+impl InputEvent {
+    /// Converts the event into its recorded form, which is a keyword followed by its fields.
+    fn to_fields(&self) -> String {
+        match self {
+            InputEvent::MouseMoved { x, y } => format!("move {} {}", x, y),
+
+            InputEvent::MouseButton { button, state } => {
+                format!("button {} {}", button, bool_to_field(*state))
+            }
+
+            InputEvent::Key {
+                keycode,
+                scancode,
+                keymod,
+                repeat,
+                state,
+            } => format!(
+                "{} {} {} {} {}",
+                if *state { "key_down" } else { "key_up" },
+                code_to_field(keycode.map(|k| k as i32)),
+                code_to_field(scancode.map(|s| s as i32)),
+                keymod.bits(),
+                bool_to_field(*repeat)
+            ),
+
+            InputEvent::TextInput { text } => format!("text {}", escape_text(text)),
+
+            InputEvent::MouseWheel { x, y } => format!("wheel {} {}", x, y),
+        }
+    }
+
+    /// Parses an event from its recorded form.
+    fn from_fields(kind: &str, fields: &str) -> Option<Self> {
+        if kind == "text" {
+            return Some(InputEvent::TextInput {
+                text: unescape_text(fields),
+            });
+        }
+
+        let values: Vec<&str> = fields.split_whitespace().collect();
+
+        match (kind, values.as_slice()) {
+            ("move", [x, y]) => Some(InputEvent::MouseMoved {
+                x: x.parse().ok()?,
+                y: y.parse().ok()?,
+            }),
+
+            ("wheel", [x, y]) => Some(InputEvent::MouseWheel {
+                x: x.parse().ok()?,
+                y: y.parse().ok()?,
+            }),
+
+            ("button", [button, state]) => Some(InputEvent::MouseButton {
+                button: button.parse().ok()?,
+                state: *state == "1",
+            }),
+
+            ("key_down", [keycode, scancode, keymod, repeat])
+            | ("key_up", [keycode, scancode, keymod, repeat]) => Some(InputEvent::Key {
+                keycode: keycode.parse().ok().and_then(Keycode::from_i32),
+                scancode: scancode.parse().ok().and_then(Scancode::from_i32),
+                keymod: Mod::from_bits_truncate(keymod.parse().ok()?),
+                repeat: *repeat == "1",
+                state: kind == "key_down",
+            }),
+
+            _ => None,
+        }
+    }
+}
+
+/// This is a recorded `InputEvent`, along with its timing: the number of frames processed since
+/// the recording started, and the number of milliseconds since the start of that frame.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordedInput {
+    pub frame: u64,
+    pub offset_ms: u128,
+    pub event: InputEvent,
+}
+
+/// This is a recorder that writes the input dispatched by an `Engine` to a file, one event per
+/// line.  It is created using `Engine::record_input`.
+pub struct InputRecorder {
+    writer: BufWriter<File>,
+    start_frame: u64,
+}
+
+/// This is an implementation of the `InputRecorder`.
+impl InputRecorder {
+    /// Creates a new recording in the file at `path`, replacing it if it already exists.
+    /// `start_frame` is the `Engine`'s frame count when the recording starts.
+    pub(crate) fn create<P: AsRef<Path>>(path: P, start_frame: u64) -> std::io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        writeln!(writer, "{}", RECORDING_HEADER)?;
+
+        Ok(Self {
+            writer,
+            start_frame,
+        })
+    }
+
+    /// Writes an `InputEvent` to the recording, received at `frame`, `offset_ms` milliseconds after
+    /// the start of the frame.
+    pub(crate) fn record(
+        &mut self,
+        frame: u64,
+        offset_ms: u128,
+        event: &InputEvent,
+    ) -> std::io::Result<()> {
+        writeln!(
+            self.writer,
+            "{} {} {}",
+            frame - self.start_frame,
+            offset_ms,
+            event.to_fields()
+        )
+    }
+
+    /// Flushes the recording to disk.
+    pub(crate) fn finish(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// This is a recording that is being played back by an `Engine`, in place of input from `SDL2`.
+/// It is created using `Engine::replay_input`.
+pub struct InputReplay {
+    inputs: VecDeque<RecordedInput>,
+    frame: u64,
+}
+
+/// This is an implementation of the `InputReplay`.
+impl InputReplay {
+    /// Loads a recording from the file at `path`.  Returns an error of kind `InvalidData` if any
+    /// line of the file cannot be parsed.
+    pub fn load<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut inputs: VecDeque<RecordedInput> = VecDeque::new();
+
+        for (line_number, line) in reader.lines().enumerate() {
+            let line = line?;
+
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid_line = || {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "Invalid input recording at line {}: {}",
+                        line_number + 1,
+                        line
+                    ),
+                )
+            };
+            let mut fields = line.splitn(4, ' ');
+            let frame = fields.next().and_then(|f| f.parse().ok());
+            let offset_ms = fields.next().and_then(|f| f.parse().ok());
+            let kind = fields.next();
+            let rest = fields.next().unwrap_or("");
+
+            match (frame, offset_ms, kind) {
+                (Some(frame), Some(offset_ms), Some(kind)) => {
+                    let event = InputEvent::from_fields(kind, rest).ok_or_else(invalid_line)?;
+
+                    inputs.push_back(RecordedInput {
+                        frame,
+                        offset_ms,
+                        event,
+                    });
+                }
+
+                _ => return Err(invalid_line()),
+            }
+        }
+
+        Ok(Self { inputs, frame: 0 })
+    }
+
+    /// Returns all of the inputs that were received before the current frame was processed, and
+    /// advances to the next frame.
+    pub(crate) fn next_frame(&mut self) -> Vec<InputEvent> {
+        let mut events: Vec<InputEvent> = Vec::new();
+
+        while let Some(input) = self.inputs.front() {
+            if input.frame > self.frame {
+                break;
+            }
+
+            events.push(self.inputs.pop_front().unwrap().event);
+        }
+
+        self.frame += 1;
+        events
+    }
+
+    /// Indicates whether or not all of the recorded inputs have been played back.
+    pub(crate) fn is_finished(&self) -> bool {
+        self.inputs.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns one of each kind of `InputEvent`, including text that needs escaping, and keys
+    /// without key or scan codes.
+    fn sample_events() -> Vec<InputEvent> {
+        vec![
+            InputEvent::MouseMoved { x: 12, y: 340 },
+            InputEvent::MouseButton {
+                button: 1,
+                state: true,
+            },
+            InputEvent::MouseButton {
+                button: 3,
+                state: false,
+            },
+            InputEvent::Key {
+                keycode: Some(Keycode::A),
+                scancode: Some(Scancode::A),
+                keymod: Mod::LSHIFTMOD | Mod::RCTRLMOD,
+                repeat: true,
+                state: true,
+            },
+            InputEvent::Key {
+                keycode: None,
+                scancode: None,
+                keymod: Mod::NOMOD,
+                repeat: false,
+                state: false,
+            },
+            InputEvent::TextInput {
+                text: String::from("  two leading spaces"),
+            },
+            InputEvent::TextInput {
+                text: String::from("back\\slash\\n and\nnew\r\nlines\\"),
+            },
+            InputEvent::TextInput {
+                text: String::new(),
+            },
+            InputEvent::MouseWheel { x: -1, y: 3 },
+        ]
+    }
+
+    #[test]
+    fn test_fields_round_trip() {
+        for event in sample_events() {
+            let fields = event.to_fields();
+            let mut parts = fields.splitn(2, ' ');
+            let kind = parts.next().unwrap();
+            let rest = parts.next().unwrap_or("");
+
+            assert!(!fields.contains('\n') && !fields.contains('\r'));
+            assert_eq!(InputEvent::from_fields(kind, rest), Some(event));
+        }
+    }
+
+    #[test]
+    fn test_invalid_fields() {
+        assert_eq!(InputEvent::from_fields("move", "12"), None);
+        assert_eq!(InputEvent::from_fields("move", "12 y"), None);
+        assert_eq!(InputEvent::from_fields("jump", "1 2"), None);
+    }
+
+    #[test]
+    fn test_recording_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("pushrod-recorder-test-{}.txt", std::process::id()));
+        let events = sample_events();
+        let mut recorder = InputRecorder::create(&path, 10).unwrap();
+
+        // Each event is recorded on its own frame, starting with the frame that the recording
+        // started on, with two events on the last frame.
+        for (i, event) in events.iter().enumerate() {
+            let frame = 10 + (i as u64).min(events.len() as u64 - 2);
+
+            recorder.record(frame, i as u128, event).unwrap();
+        }
+
+        recorder.finish().unwrap();
+        drop(recorder);
+
+        let mut replay = InputReplay::load(&path).unwrap();
+
+        std::fs::remove_file(&path).unwrap();
+
+        for event in events.iter().take(events.len() - 2) {
+            assert!(!replay.is_finished());
+            assert_eq!(replay.next_frame(), vec![event.clone()]);
+        }
+
+        assert_eq!(replay.next_frame(), events[events.len() - 2..].to_vec());
+        assert!(replay.is_finished());
+        assert!(replay.next_frame().is_empty());
+    }
+
+    #[test]
+    fn test_replay_frame_alignment() {
+        let path =
+            std::env::temp_dir().join(format!("pushrod-replay-test-{}.txt", std::process::id()));
+
+        std::fs::write(
+            &path,
+            format!(
+                "{}\n0 0 move 1 1\n0 5 move 2 2\n\n3 1 button 1 1\n",
+                RECORDING_HEADER
+            ),
+        )
+        .unwrap();
+
+        let mut replay = InputReplay::load(&path).unwrap();
+
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            replay.next_frame(),
+            vec![
+                InputEvent::MouseMoved { x: 1, y: 1 },
+                InputEvent::MouseMoved { x: 2, y: 2 },
+            ]
+        );
+        assert!(replay.next_frame().is_empty());
+        assert!(replay.next_frame().is_empty());
+        assert_eq!(
+            replay.next_frame(),
+            vec![InputEvent::MouseButton {
+                button: 1,
+                state: true,
+            }]
+        );
+        assert!(replay.is_finished());
+    }
+
+    #[test]
+    fn test_invalid_recording_line() {
+        let path = std::env::temp_dir().join(format!(
+            "pushrod-invalid-replay-test-{}.txt",
+            std::process::id()
+        ));
+
+        std::fs::write(&path, "0 0 move 1 1\n1 move 2 2\n").unwrap();
+
+        let error = InputReplay::load(&path).err().unwrap();
+
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert!(error.to_string().contains("line 2"));
+    }
+}
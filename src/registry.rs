@@ -0,0 +1,129 @@
+// Pushrod Registry
+// Widget Class Registry
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pushrod_widgets::system_widgets::base_widget::BaseWidget;
+use pushrod_widgets::system_widgets::box_widget::BoxWidget;
+use pushrod_widgets::system_widgets::button_widget::ButtonWidget;
+use pushrod_widgets::system_widgets::checkbox_widget::CheckBoxWidget;
+use pushrod_widgets::system_widgets::grid_widget::GridWidget;
+use pushrod_widgets::system_widgets::group_box_widget::GroupBoxWidget;
+use pushrod_widgets::system_widgets::image_button_widget::ImageButtonWidget;
+use pushrod_widgets::system_widgets::image_widget::ImageWidget;
+use pushrod_widgets::system_widgets::menu_item_widget::MenuItemWidget;
+use pushrod_widgets::system_widgets::popup_menu_widget::PopupMenuWidget;
+use pushrod_widgets::system_widgets::progress_widget::ProgressWidget;
+use pushrod_widgets::system_widgets::radio_button_widget::RadioButtonWidget;
+use pushrod_widgets::system_widgets::slider_widget::SliderWidget;
+use pushrod_widgets::system_widgets::text_widget::TextWidget;
+use pushrod_widgets::system_widgets::tile_widget::TileWidget;
+use pushrod_widgets::system_widgets::toggle_button_widget::ToggleButtonWidget;
+use pushrod_widgets::widget::Widget;
+use std::collections::HashMap;
+
+/// This is the registry of `Widget` classes, which creates new `Widget`s from their class names,
+/// such as the `class` given to each `Widget` in a `.rsc` layout.  The default registry contains
+/// every `Widget` in `pushrod_widgets::system_widgets`, registered by its type name, such as
+/// `GridWidget`.
+///
+/// Applications can register their own `Widget`s, so that they can be used in layouts as well:
+///
+/// ```rust,no_run
+/// use pushrod::layout::load_layout_with;
+/// use pushrod::registry::WidgetRegistry;
+/// # use pushrod_widgets::caches::WidgetCache;
+/// # use pushrod_widgets::system_widgets::box_widget::BoxWidget;
+/// # fn build_layout(cache: &mut WidgetCache) {
+///
+/// let registry = WidgetRegistry::default()
+///     .register("RoundedBoxWidget", || Box::new(BoxWidget::default()));
+/// let ids = load_layout_with("assets/custom.rsc", cache, &registry).unwrap();
+/// # }
+/// ```
+pub struct WidgetRegistry {
+    constructors: HashMap<String, Box<dyn Fn() -> Box<dyn Widget>>>,
+}
+
+/// The default registry contains all of the system `Widget`s.
+impl Default for WidgetRegistry {
+    fn default() -> Self {
+        Self::empty()
+            .register_default::<BaseWidget>("BaseWidget")
+            .register_default::<BoxWidget>("BoxWidget")
+            .register_default::<ButtonWidget>("ButtonWidget")
+            .register_default::<CheckBoxWidget>("CheckBoxWidget")
+            .register_default::<GridWidget>("GridWidget")
+            .register_default::<GroupBoxWidget>("GroupBoxWidget")
+            .register_default::<ImageButtonWidget>("ImageButtonWidget")
+            .register_default::<ImageWidget>("ImageWidget")
+            .register_default::<MenuItemWidget>("MenuItemWidget")
+            .register_default::<PopupMenuWidget>("PopupMenuWidget")
+            .register_default::<ProgressWidget>("ProgressWidget")
+            .register_default::<RadioButtonWidget>("RadioButtonWidget")
+            .register_default::<SliderWidget>("SliderWidget")
+            .register_default::<TextWidget>("TextWidget")
+            .register_default::<TileWidget>("TileWidget")
+            .register_default::<ToggleButtonWidget>("ToggleButtonWidget")
+    }
+}
+
+/// This is an implementation of the `WidgetRegistry` builder.
+impl WidgetRegistry {
+    /// Creates a registry without any classes registered.
+    pub fn empty() -> Self {
+        Self {
+            constructors: HashMap::new(),
+        }
+    }
+
+    /// Registers a `Widget` class under `class_name`, created by calling `constructor`.  If the
+    /// class name is already registered, its constructor is replaced.
+    pub fn register<F>(mut self, class_name: &str, constructor: F) -> Self
+    where
+        F: Fn() -> Box<dyn Widget> + 'static,
+    {
+        self.constructors
+            .insert(String::from(class_name), Box::new(constructor));
+        self
+    }
+
+    /// Registers a `Widget` class under `class_name`, created using its `Default` implementation.
+    pub fn register_default<W>(self, class_name: &str) -> Self
+    where
+        W: Widget + Default + 'static,
+    {
+        self.register(class_name, || Box::new(W::default()))
+    }
+
+    /// Creates a new `Widget` of the class registered as `class_name`.  Returns `None` if the
+    /// class name is not registered.
+    pub fn create(&self, class_name: &str) -> Option<Box<dyn Widget>> {
+        self.constructors
+            .get(class_name)
+            .map(|constructor| constructor())
+    }
+
+    /// Indicates whether or not a class is registered as `class_name`.
+    pub fn contains(&self, class_name: &str) -> bool {
+        self.constructors.contains_key(class_name)
+    }
+
+    /// Returns the registered class names, sorted alphabetically.
+    pub fn class_names(&self) -> Vec<String> {
+        let mut class_names: Vec<String> = self.constructors.keys().cloned().collect();
+
+        class_names.sort();
+        class_names
+    }
+}
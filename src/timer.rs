@@ -0,0 +1,253 @@
+// Pushrod Timers
+// Timer Service
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// This is a single scheduled timer.  Repeating timers have an `interval`, and are rescheduled
+/// each time they fire.
+struct Timer {
+    timer_id: u32,
+    deadline: u128,
+    interval: Option<u128>,
+    widget_id: Option<u32>,
+}
+
+/// This is the list of scheduled timers, along with the current time of the `Engine`, from which
+/// the deadlines of new timers are calculated.
+#[derive(Default)]
+struct TimerQueue {
+    next_timer_id: u32,
+    now: u128,
+    timers: Vec<Timer>,
+}
+
+/// This is the timer service for an `Engine`.  Timers are checked on every frame, and each time a
+/// timer fires, a `TimerFired` event is sent to the `EventHandler` containing its timer ID, and
+/// the ID of the `Widget` that it targets, if any.  Delays are measured from the time the timer is
+/// scheduled, and timers are accurate to the length of a frame.
+///
+/// `Timers` can be cloned, and all clones share the same timers, so a copy can be kept in the
+/// `EventHandler`, and another given to the `Engine` using `Engine::set_timers`:
+///
+/// ```rust,no_run
+/// use pushrod::timer::Timers;
+///
+/// let timers = Timers::default();
+/// let poll_timer_id = timers.repeat(1000, None);
+///
+/// timers.cancel(poll_timer_id);
+/// ```
+#[derive(Clone, Default)]
+pub struct Timers {
+    queue: Rc<RefCell<TimerQueue>>,
+}
+
+/// This is an implementation of the `Timers` service.
+impl Timers {
+    /// Adds a timer to the queue, returning its timer ID.
+    fn schedule(&self, delay_ms: u128, interval: Option<u128>, widget_id: Option<u32>) -> u32 {
+        let mut queue = self.queue.borrow_mut();
+
+        queue.next_timer_id += 1;
+
+        let timer = Timer {
+            timer_id: queue.next_timer_id,
+            deadline: queue.now + delay_ms,
+            interval,
+            widget_id,
+        };
+
+        queue.timers.push(timer);
+        queue.next_timer_id
+    }
+
+    /// Schedules a timer that fires once, after `delay_ms` milliseconds.  If `widget_id` is
+    /// specified, the `TimerFired` event is targeted at that `Widget`.  Returns the ID of the timer.
+    pub fn once(&self, delay_ms: u128, widget_id: Option<u32>) -> u32 {
+        self.schedule(delay_ms, None, widget_id)
+    }
+
+    /// Schedules a timer that fires every `interval_ms` milliseconds until it is cancelled.  If
+    /// `widget_id` is specified, the `TimerFired` events are targeted at that `Widget`.  Returns the
+    /// ID of the timer.
+    pub fn repeat(&self, interval_ms: u128, widget_id: Option<u32>) -> u32 {
+        let interval_ms = interval_ms.max(1);
+
+        self.schedule(interval_ms, Some(interval_ms), widget_id)
+    }
+
+    /// Cancels the timer with the ID `timer_id`.  Returns `true` if the timer was scheduled, or
+    /// `false` if it had already fired or been cancelled.
+    pub fn cancel(&self, timer_id: u32) -> bool {
+        let mut queue = self.queue.borrow_mut();
+        let timer_count = queue.timers.len();
+
+        queue.timers.retain(|t| t.timer_id != timer_id);
+        queue.timers.len() != timer_count
+    }
+
+    /// Indicates whether or not any timers are scheduled.
+    pub fn is_active(&self) -> bool {
+        !self.queue.borrow().timers.is_empty()
+    }
+
+    /// Advances the time from which the deadlines of new timers are calculated to `now`, without
+    /// firing any timers.  The `Engine` calls this before dispatching input, so that a timer
+    /// scheduled in response is measured from the time of the input, rather than from the last
+    /// frame.  The time never goes backwards.
+    pub(crate) fn set_now(&self, now: u128) {
+        let mut queue = self.queue.borrow_mut();
+
+        queue.now = queue.now.max(now);
+    }
+
+    /// Returns the time at which the next timer is due to fire, if any timers are scheduled.
+    pub(crate) fn next_deadline(&self) -> Option<u128> {
+        self.queue.borrow().timers.iter().map(|t| t.deadline).min()
+    }
+
+    /// Advances the time of the queue to `now`, returning the timer ID and target `Widget` ID of
+    /// every timer that is due, in the order of their deadlines.  One shot timers are removed from
+    /// the queue, and repeating timers are rescheduled for their next interval.  If a repeating
+    /// timer has missed more than one interval, it only fires once.
+    pub(crate) fn expire(&self, now: u128) -> Vec<(u32, Option<u32>)> {
+        let mut queue = self.queue.borrow_mut();
+        let mut expired: Vec<(u128, u32, Option<u32>)> = Vec::new();
+
+        queue.now = queue.now.max(now);
+
+        for timer in queue.timers.iter_mut() {
+            if timer.deadline > now {
+                continue;
+            }
+
+            expired.push((timer.deadline, timer.timer_id, timer.widget_id));
+
+            if let Some(interval) = timer.interval {
+                while timer.deadline <= now {
+                    timer.deadline += interval;
+                }
+            }
+        }
+
+        queue
+            .timers
+            .retain(|t| t.interval.is_some() || t.deadline > now);
+
+        expired.sort_by_key(|(deadline, _, _)| *deadline);
+        expired
+            .into_iter()
+            .map(|(_, timer_id, widget_id)| (timer_id, widget_id))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_once_fires_once() {
+        let timers = Timers::default();
+        let timer_id = timers.once(100, Some(5));
+
+        assert_eq!(timers.next_deadline(), Some(100));
+        assert!(timers.expire(99).is_empty());
+        assert_eq!(timers.expire(100), vec![(timer_id, Some(5))]);
+        assert!(timers.expire(200).is_empty());
+        assert!(!timers.is_active());
+    }
+
+    #[test]
+    fn test_repeat_reschedules() {
+        let timers = Timers::default();
+        let timer_id = timers.repeat(100, None);
+
+        assert_eq!(timers.expire(100), vec![(timer_id, None)]);
+        assert_eq!(timers.next_deadline(), Some(200));
+        assert!(timers.expire(150).is_empty());
+
+        // Missing several intervals only fires the timer once.
+        assert_eq!(timers.expire(450), vec![(timer_id, None)]);
+        assert_eq!(timers.next_deadline(), Some(500));
+        assert!(timers.is_active());
+    }
+
+    #[test]
+    fn test_repeat_zero_interval() {
+        let timers = Timers::default();
+
+        timers.repeat(0, None);
+
+        assert_eq!(timers.next_deadline(), Some(1));
+    }
+
+    #[test]
+    fn test_cancel() {
+        let timers = Timers::default();
+        let once_id = timers.once(100, None);
+        let repeat_id = timers.repeat(50, None);
+
+        assert!(timers.cancel(once_id));
+        assert!(!timers.cancel(once_id));
+        assert_eq!(timers.expire(100), vec![(repeat_id, None)]);
+        assert!(timers.cancel(repeat_id));
+        assert!(timers.expire(1000).is_empty());
+        assert!(!timers.is_active());
+    }
+
+    #[test]
+    fn test_expire_orders_by_deadline() {
+        let timers = Timers::default();
+        let late_id = timers.once(300, None);
+        let early_id = timers.once(100, None);
+        let middle_id = timers.once(200, Some(2));
+
+        assert_eq!(
+            timers.expire(300),
+            vec![(early_id, None), (middle_id, Some(2)), (late_id, None)]
+        );
+    }
+
+    #[test]
+    fn test_deadline_relative_to_last_expire() {
+        let timers = Timers::default();
+
+        timers.expire(1000);
+        timers.once(100, None);
+
+        assert_eq!(timers.next_deadline(), Some(1100));
+    }
+
+    #[test]
+    fn test_deadline_relative_to_set_now() {
+        let timers = Timers::default();
+
+        timers.expire(100);
+        timers.set_now(1000);
+
+        let timer_id = timers.once(500, None);
+
+        assert!(timers.expire(1100).is_empty());
+        assert_eq!(timers.expire(1500), vec![(timer_id, None)]);
+
+        // The time never goes backwards.
+        timers.set_now(200);
+        timers.once(100, None);
+
+        assert_eq!(timers.next_deadline(), Some(1600));
+    }
+}
@@ -0,0 +1,99 @@
+// Pushrod Headless Engine Tests
+// Mouse Capture
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// These tests drive a real `Engine` through the `dummy` video driver, so they must be run with
+// `SDL_VIDEODRIVER=dummy cargo test -- --test-threads=1`.
+
+mod common;
+
+use common::{add_widget, start, Recorded};
+use pushrod::headless::HeadlessEngine;
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// Starts an engine with two `Widget`s side by side, returning their IDs.
+fn start_with_two_widgets() -> (HeadlessEngine, Recorded, u32, u32) {
+    let ids = Rc::new(Cell::new((0, 0)));
+    let layout_ids = ids.clone();
+    let (mut headless, recorded) = start(move |cache| {
+        layout_ids.set((
+            add_widget(cache, 0, (10, 10), (40, 40), |_| ()),
+            add_widget(cache, 0, (100, 10), (40, 40), |_| ()),
+        ));
+    });
+    let (left_id, right_id) = ids.get();
+
+    headless.step();
+
+    (headless, recorded, left_id, right_id)
+}
+
+#[test]
+fn test_release_goes_to_pressed_widget() {
+    let (mut headless, recorded, left_id, right_id) = start_with_two_widgets();
+
+    headless.mouse_move(20, 20);
+    headless.mouse_button(1, true);
+
+    assert_eq!(headless.get_engine().get_captured_widget(), Some(left_id));
+
+    // The release is delivered to the pressed Widget, even though the pointer has left it.
+    headless.mouse_move(110, 20);
+
+    assert_eq!(headless.get_engine().get_captured_widget(), Some(left_id));
+
+    headless.mouse_button(1, false);
+
+    assert_eq!(headless.get_engine().get_captured_widget(), None);
+    assert_eq!(
+        recorded.take_mouse_buttons(),
+        vec![(left_id, true), (left_id, false)]
+    );
+
+    // Once released, the next press goes to the Widget under the pointer.
+    headless.mouse_button(1, true);
+    headless.mouse_button(1, false);
+
+    assert_eq!(
+        recorded.take_mouse_buttons(),
+        vec![(right_id, true), (right_id, false)]
+    );
+}
+
+#[test]
+fn test_capture_held_until_all_buttons_released() {
+    let (mut headless, recorded, left_id, _) = start_with_two_widgets();
+
+    headless.mouse_move(20, 20);
+    headless.mouse_button(1, true);
+    headless.mouse_move(110, 20);
+    headless.mouse_button(3, true);
+    headless.mouse_button(1, false);
+
+    assert_eq!(headless.get_engine().get_captured_widget(), Some(left_id));
+
+    headless.mouse_button(3, false);
+
+    assert_eq!(headless.get_engine().get_captured_widget(), None);
+    assert_eq!(
+        recorded.take_mouse_buttons(),
+        vec![
+            (left_id, true),
+            (left_id, true),
+            (left_id, false),
+            (left_id, false)
+        ]
+    );
+}
@@ -0,0 +1,123 @@
+// Pushrod Headless Engine Tests
+// Common Test Support
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Not every test uses all of the support functions.
+#![allow(dead_code)]
+
+use pushrod::engine::EventHandler;
+use pushrod::event::EngineEvent;
+use pushrod::headless::HeadlessEngine;
+use pushrod_widgets::caches::WidgetCache;
+use pushrod_widgets::event::{Event, PushrodEvent};
+use pushrod_widgets::properties::WidgetProperties;
+use pushrod_widgets::system_widgets::base_widget::BaseWidget;
+use pushrod_widgets::widget::Widget;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// These are the events received by a `RecordingHandler`, shared with the test.
+#[derive(Clone, Default)]
+pub struct Recorded {
+    engine_events: Rc<RefCell<Vec<EngineEvent>>>,
+    mouse_buttons: Rc<RefCell<Vec<(u32, bool)>>>,
+}
+
+impl Recorded {
+    /// Returns the `EngineEvent`s received since the last call, other than `FrameTick`s.
+    pub fn take_engine_events(&self) -> Vec<EngineEvent> {
+        self.engine_events
+            .borrow_mut()
+            .drain(..)
+            .filter(|event| !matches!(event, EngineEvent::FrameTick { .. }))
+            .collect()
+    }
+
+    /// Returns the `Widget` ID and state of each `MouseButton` event received since the last call.
+    pub fn take_mouse_buttons(&self) -> Vec<(u32, bool)> {
+        self.mouse_buttons.borrow_mut().drain(..).collect()
+    }
+}
+
+/// This is an `EventHandler` that builds its layout using a closure, and records the events that
+/// it receives.
+struct RecordingHandler {
+    build: Box<dyn FnMut(&mut WidgetCache)>,
+    recorded: Recorded,
+}
+
+impl EventHandler for RecordingHandler {
+    fn handle_event(&mut self, event: Event, _cache: &mut WidgetCache) {
+        if let Event::Pushrod(PushrodEvent::MouseButton {
+            widget_id, state, ..
+        }) = event
+        {
+            self.recorded
+                .mouse_buttons
+                .borrow_mut()
+                .push((widget_id, state));
+        }
+    }
+
+    fn handle_engine_event(&mut self, event: EngineEvent, _cache: &mut WidgetCache) {
+        self.recorded.engine_events.borrow_mut().push(event);
+    }
+
+    fn build_layout(&mut self, cache: &mut WidgetCache) {
+        (self.build)(cache);
+    }
+}
+
+/// Creates a 200 x 200 `HeadlessEngine` using the `dummy` video driver, whose layout is built by
+/// `build`, returning it along with the events that its handler receives.
+pub fn start<F>(build: F) -> (HeadlessEngine, Recorded)
+where
+    F: FnMut(&mut WidgetCache) + 'static,
+{
+    std::env::set_var("SDL_VIDEODRIVER", "dummy");
+
+    let recorded = Recorded::default();
+    let handler = RecordingHandler {
+        build: Box::new(build),
+        recorded: recorded.clone(),
+    };
+    let headless = HeadlessEngine::new(Box::new(handler), 200, 200).unwrap();
+
+    (headless, recorded)
+}
+
+/// Adds a `BaseWidget` to the `cache` under `parent_id`, at `origin` with the size `bounds`,
+/// calling `setup` to set any other properties.  Returns the ID of the `Widget`.
+pub fn add_widget<F>(
+    cache: &mut WidgetCache,
+    parent_id: u32,
+    origin: (u32, u32),
+    bounds: (u32, u32),
+    setup: F,
+) -> u32
+where
+    F: FnOnce(&mut WidgetProperties),
+{
+    let mut widget = BaseWidget::default();
+
+    widget
+        .properties()
+        .set_origin(origin.0, origin.1)
+        .set_bounds(bounds.0, bounds.1);
+    setup(widget.properties());
+
+    let widget_id = cache.size();
+
+    cache.add(Box::new(widget), format!("widget{}", widget_id), parent_id)
+}
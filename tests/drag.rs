@@ -0,0 +1,173 @@
+// Pushrod Headless Engine Tests
+// Drag and Drop
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// These tests drive a real `Engine` through the `dummy` video driver, so they must be run with
+// `SDL_VIDEODRIVER=dummy cargo test -- --test-threads=1`.
+
+mod common;
+
+use common::{add_widget, start, Recorded};
+use pushrod::event::EngineEvent;
+use pushrod::headless::HeadlessEngine;
+use pushrod::properties::{PROPERTY_DRAG_PAYLOAD, PROPERTY_DROP_TARGET};
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// These are the IDs of the `Widget` that can be dragged, and of the drop target.
+#[derive(Clone, Copy, Default)]
+struct DragIds {
+    source: u32,
+    target: u32,
+}
+
+/// Starts an engine with four 40 x 40 `Widget`s: one that can be dragged at (10, 10), one without
+/// a payload at (10, 100), a drop target at (100, 10), and one that is not a drop target at
+/// (100, 100).
+fn start_drag_layout() -> (HeadlessEngine, Recorded, DragIds) {
+    let ids = Rc::new(Cell::new(DragIds::default()));
+    let layout_ids = ids.clone();
+    let (mut headless, recorded) = start(move |cache| {
+        let source = add_widget(cache, 0, (10, 10), (40, 40), |p| {
+            p.set(PROPERTY_DRAG_PAYLOAD, String::from("payload"));
+        });
+
+        add_widget(cache, 0, (10, 100), (40, 40), |_| ());
+
+        let target = add_widget(cache, 0, (100, 10), (40, 40), |p| {
+            p.set_bool(PROPERTY_DROP_TARGET);
+        });
+
+        add_widget(cache, 0, (100, 100), (40, 40), |_| ());
+        layout_ids.set(DragIds { source, target });
+    });
+
+    headless.step();
+
+    (headless, recorded, ids.get())
+}
+
+#[test]
+fn test_drag_threshold() {
+    let (mut headless, recorded, ids) = start_drag_layout();
+
+    // The default threshold is 4 pixels, so smaller movements are not a drag.
+    headless.mouse_move(20, 20);
+    headless.mouse_button(1, true);
+    headless.mouse_move(23, 17);
+    headless.mouse_button(1, false);
+
+    assert!(recorded.take_engine_events().is_empty());
+
+    // Moving 4 pixels from where the button was pressed starts the drag.
+    headless.mouse_button(1, true);
+    headless.mouse_move(27, 17);
+
+    assert_eq!(
+        recorded.take_engine_events(),
+        vec![EngineEvent::DragStarted {
+            widget_id: ids.source,
+            payload: String::from("payload"),
+        }]
+    );
+
+    headless.mouse_button(1, false);
+}
+
+#[test]
+fn test_no_payload() {
+    let (mut headless, recorded, _) = start_drag_layout();
+
+    headless.mouse_move(20, 110);
+    headless.mouse_button(1, true);
+    headless.mouse_move(110, 20);
+    headless.mouse_button(1, false);
+
+    assert!(recorded.take_engine_events().is_empty());
+}
+
+#[test]
+fn test_drag_over_leave_and_drop() {
+    let (mut headless, recorded, ids) = start_drag_layout();
+    let drag_over = |x, y| EngineEvent::DragOver {
+        widget_id: ids.target,
+        source_widget_id: ids.source,
+        x,
+        y,
+    };
+
+    headless.mouse_move(20, 20);
+    headless.mouse_button(1, true);
+    headless.mouse_move(110, 25);
+
+    assert_eq!(
+        recorded.take_engine_events(),
+        vec![
+            EngineEvent::DragStarted {
+                widget_id: ids.source,
+                payload: String::from("payload"),
+            },
+            drag_over(10, 15),
+        ]
+    );
+
+    headless.mouse_move(110, 110);
+
+    assert_eq!(
+        recorded.take_engine_events(),
+        vec![EngineEvent::DragLeave {
+            widget_id: ids.target,
+            source_widget_id: ids.source,
+        }]
+    );
+
+    headless.mouse_move(105, 15);
+    headless.mouse_button(1, false);
+
+    assert_eq!(
+        recorded.take_engine_events(),
+        vec![
+            drag_over(5, 5),
+            EngineEvent::Dropped {
+                widget_id: ids.target,
+                source_widget_id: ids.source,
+                payload: String::from("payload"),
+                accepted: true,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_drag_cancelled() {
+    let (mut headless, recorded, ids) = start_drag_layout();
+
+    headless.mouse_move(20, 20);
+    headless.mouse_button(1, true);
+    headless.mouse_move(110, 110);
+    headless.mouse_button(1, false);
+
+    assert_eq!(
+        recorded.take_engine_events(),
+        vec![
+            EngineEvent::DragStarted {
+                widget_id: ids.source,
+                payload: String::from("payload"),
+            },
+            EngineEvent::DragCancelled {
+                source_widget_id: ids.source,
+            },
+        ]
+    );
+}
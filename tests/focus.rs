@@ -0,0 +1,179 @@
+// Pushrod Headless Engine Tests
+// Keyboard Focus
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// These tests drive a real `Engine` through the `dummy` video driver, so they must be run with
+// `SDL_VIDEODRIVER=dummy cargo test -- --test-threads=1`.
+
+mod common;
+
+use common::{add_widget, start};
+use pushrod::event::EngineEvent;
+use pushrod::headless::HeadlessEngine;
+use pushrod::properties::{PROPERTY_FOCUSABLE, PROPERTY_TAB_INDEX};
+use pushrod_widgets::properties::{PROPERTY_DISABLED, PROPERTY_HIDDEN};
+use sdl2::keyboard::{Keycode, Mod};
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// Presses and releases `Tab`, holding `Shift` if `reverse` is set, and returns the ID of the
+/// `Widget` that has the focus afterward.
+fn tab(headless: &mut HeadlessEngine, reverse: bool) -> u32 {
+    let keymod = if reverse { Mod::LSHIFTMOD } else { Mod::NOMOD };
+
+    headless.key(Keycode::Tab, keymod, true);
+    headless.key(Keycode::Tab, keymod, false);
+    headless.get_engine().get_focused_widget()
+}
+
+#[test]
+fn test_tab_index_order() {
+    let ids = Rc::new(Cell::new([0; 4]));
+    let layout_ids = ids.clone();
+    let (mut headless, _) = start(move |cache| {
+        layout_ids.set([
+            add_widget(cache, 0, (0, 0), (10, 10), |p| {
+                p.set_bool(PROPERTY_FOCUSABLE);
+            }),
+            add_widget(cache, 0, (10, 0), (10, 10), |p| {
+                p.set_value(PROPERTY_TAB_INDEX, 2);
+            }),
+            add_widget(cache, 0, (20, 0), (10, 10), |p| {
+                p.set_value(PROPERTY_TAB_INDEX, 1);
+            }),
+            add_widget(cache, 0, (30, 0), (10, 10), |p| {
+                p.set_bool(PROPERTY_FOCUSABLE);
+            }),
+        ]);
+    });
+    let [unindexed1, index2, index1, unindexed2] = ids.get();
+
+    // Indexed Widgets come first, then the rest in cache order, wrapping around at the end.
+    assert_eq!(tab(&mut headless, false), index1);
+    assert_eq!(tab(&mut headless, false), index2);
+    assert_eq!(tab(&mut headless, false), unindexed1);
+    assert_eq!(tab(&mut headless, false), unindexed2);
+    assert_eq!(tab(&mut headless, false), index1);
+
+    // Shift-Tab goes backward, wrapping around at the start.
+    assert_eq!(tab(&mut headless, true), unindexed2);
+    assert_eq!(tab(&mut headless, true), unindexed1);
+}
+
+#[test]
+fn test_skips_hidden_and_disabled() {
+    let ids = Rc::new(Cell::new([0; 2]));
+    let layout_ids = ids.clone();
+    let (mut headless, _) = start(move |cache| {
+        let first = add_widget(cache, 0, (0, 0), (10, 10), |p| {
+            p.set_bool(PROPERTY_FOCUSABLE);
+        });
+
+        add_widget(cache, 0, (10, 0), (10, 10), |p| {
+            p.set_bool(PROPERTY_FOCUSABLE).set_bool(PROPERTY_DISABLED);
+        });
+        add_widget(cache, 0, (20, 0), (10, 10), |p| {
+            p.set_bool(PROPERTY_FOCUSABLE).set_bool(PROPERTY_HIDDEN);
+        });
+
+        let hidden_parent = add_widget(cache, 0, (30, 0), (20, 20), |p| {
+            p.set_bool(PROPERTY_HIDDEN);
+        });
+
+        add_widget(cache, hidden_parent, (30, 0), (10, 10), |p| {
+            p.set_bool(PROPERTY_FOCUSABLE);
+        });
+
+        let last = add_widget(cache, 0, (60, 0), (10, 10), |p| {
+            p.set_value(PROPERTY_TAB_INDEX, 1);
+        });
+
+        layout_ids.set([first, last]);
+    });
+    let [first, last] = ids.get();
+
+    assert_eq!(tab(&mut headless, false), last);
+    assert_eq!(tab(&mut headless, false), first);
+    assert_eq!(tab(&mut headless, false), last);
+}
+
+#[test]
+fn test_no_focusable_widgets() {
+    let (mut headless, recorded) = start(|cache| {
+        add_widget(cache, 0, (0, 0), (10, 10), |_| ());
+    });
+
+    assert_eq!(tab(&mut headless, false), 0);
+    assert!(recorded.take_engine_events().is_empty());
+}
+
+#[test]
+fn test_tab_release_swallowed() {
+    let widget_id = Rc::new(Cell::new(0));
+    let layout_widget_id = widget_id.clone();
+    let (mut headless, recorded) = start(move |cache| {
+        layout_widget_id.set(add_widget(cache, 0, (0, 0), (10, 10), |p| {
+            p.set_bool(PROPERTY_FOCUSABLE);
+        }));
+    });
+    let widget_id = widget_id.get();
+
+    // Neither the Tab that moved the focus, nor its release, is sent on, and no focus event is
+    // sent for the top-level Widget that had the focus before.
+    tab(&mut headless, false);
+
+    assert_eq!(
+        recorded.take_engine_events(),
+        vec![EngineEvent::WidgetFocusGained { widget_id }]
+    );
+
+    // Other keys are sent with the ID of the focused Widget.
+    headless.key(Keycode::A, Mod::NOMOD, true);
+    headless.key(Keycode::A, Mod::NOMOD, false);
+
+    let events = recorded.take_engine_events();
+
+    assert_eq!(events.len(), 2);
+    assert!(matches!(
+        events[0],
+        EngineEvent::KeyPressed { widget_id: id, keycode: Some(Keycode::A), .. } if id == widget_id
+    ));
+    assert!(matches!(
+        events[1],
+        EngineEvent::KeyReleased { widget_id: id, keycode: Some(Keycode::A), .. } if id == widget_id
+    ));
+
+    // Ctrl-Tab does not move the focus, so both its press and release are sent on.
+    headless.key(Keycode::Tab, Mod::LCTRLMOD, true);
+    headless.key(Keycode::Tab, Mod::LCTRLMOD, false);
+
+    let events = recorded.take_engine_events();
+
+    assert_eq!(headless.get_engine().get_focused_widget(), widget_id);
+    assert_eq!(events.len(), 2);
+    assert!(matches!(
+        events[0],
+        EngineEvent::KeyPressed {
+            keycode: Some(Keycode::Tab),
+            ..
+        }
+    ));
+    assert!(matches!(
+        events[1],
+        EngineEvent::KeyReleased {
+            keycode: Some(Keycode::Tab),
+            ..
+        }
+    ));
+}
@@ -0,0 +1,177 @@
+// Pushrod Headless Engine Tests
+// Headless Engine
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// These tests drive a real `Engine` through the `dummy` video driver, so they must be run with
+// `SDL_VIDEODRIVER=dummy cargo test -- --test-threads=1`.
+
+use pushrod::clock::Clock;
+use pushrod::engine::EventHandler;
+use pushrod::event::EngineEvent;
+use pushrod::headless::HeadlessEngine;
+use pushrod::properties::PROPERTY_FOCUSABLE;
+use pushrod::timer::Timers;
+use pushrod_widgets::caches::WidgetCache;
+use pushrod_widgets::properties::PROPERTY_MAIN_COLOR;
+use pushrod_widgets::system_widgets::base_widget::BaseWidget;
+use pushrod_widgets::widget::Widget;
+use sdl2::pixels::Color;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// Builds a single focusable box, and turns it red when it gains the keyboard focus.
+struct ClickHandler {
+    box_id: u32,
+    focus_count: Rc<Cell<u32>>,
+}
+
+impl EventHandler for ClickHandler {
+    fn handle_engine_event(&mut self, event: EngineEvent, cache: &mut WidgetCache) {
+        if let EngineEvent::WidgetFocusGained { widget_id } = event {
+            if widget_id == self.box_id {
+                self.focus_count.set(self.focus_count.get() + 1);
+
+                let mut widget = cache.get(widget_id);
+
+                widget
+                    .properties()
+                    .set_color(PROPERTY_MAIN_COLOR, Color::RGB(255, 0, 0));
+                widget.invalidate();
+            }
+        }
+    }
+
+    fn build_layout(&mut self, cache: &mut WidgetCache) {
+        let mut base_widget = BaseWidget::default();
+
+        base_widget
+            .properties()
+            .set_origin(10, 10)
+            .set_bounds(40, 40)
+            .set_color(PROPERTY_MAIN_COLOR, Color::RGB(255, 255, 255))
+            .set_bool(PROPERTY_FOCUSABLE);
+
+        self.box_id = cache.add(Box::new(base_widget), String::from("box"), 0);
+    }
+}
+
+/// Builds a single focusable box, and schedules a timer when it gains the keyboard focus.
+struct TimerHandler {
+    timers: Timers,
+    fired_count: Rc<Cell<u32>>,
+}
+
+impl EventHandler for TimerHandler {
+    fn handle_engine_event(&mut self, event: EngineEvent, _cache: &mut WidgetCache) {
+        match event {
+            EngineEvent::WidgetFocusGained { .. } => {
+                self.timers.once(500, None);
+            }
+
+            EngineEvent::TimerFired { .. } => self.fired_count.set(self.fired_count.get() + 1),
+
+            _ => (),
+        }
+    }
+
+    fn build_layout(&mut self, cache: &mut WidgetCache) {
+        let mut base_widget = BaseWidget::default();
+
+        base_widget
+            .properties()
+            .set_origin(10, 10)
+            .set_bounds(40, 40)
+            .set_bool(PROPERTY_FOCUSABLE);
+
+        cache.add(Box::new(base_widget), String::from("box"), 0);
+    }
+}
+
+#[test]
+fn test_click_redraws_focused_widget() {
+    std::env::set_var("SDL_VIDEODRIVER", "dummy");
+
+    let focus_count = Rc::new(Cell::new(0));
+    let handler = ClickHandler {
+        box_id: 0,
+        focus_count: focus_count.clone(),
+    };
+    let mut headless = HeadlessEngine::new(Box::new(handler), 100, 100).unwrap();
+
+    headless.step_frames(2);
+
+    assert_eq!(
+        headless.pixel_at(30, 30).unwrap(),
+        Color::RGB(255, 255, 255)
+    );
+    assert_eq!(focus_count.get(), 0);
+
+    headless.mouse_move(30, 30);
+    headless.mouse_button(1, true);
+    headless.mouse_button(1, false);
+    headless.step();
+
+    assert_eq!(focus_count.get(), 1);
+    assert_eq!(headless.pixel_at(30, 30).unwrap(), Color::RGB(255, 0, 0));
+
+    // Clicking outside of the box leaves it untouched.
+    headless.mouse_move(80, 80);
+    headless.mouse_button(1, true);
+    headless.mouse_button(1, false);
+    headless.step();
+
+    assert_eq!(focus_count.get(), 1);
+    assert_eq!(headless.pixel_at(30, 30).unwrap(), Color::RGB(255, 0, 0));
+}
+
+#[test]
+fn test_timer_measured_from_input() {
+    std::env::set_var("SDL_VIDEODRIVER", "dummy");
+
+    let timers = Timers::default();
+    let fired_count = Rc::new(Cell::new(0));
+    let handler = TimerHandler {
+        timers: timers.clone(),
+        fired_count: fired_count.clone(),
+    };
+    let mut headless = HeadlessEngine::new(Box::new(handler), 100, 100).unwrap();
+
+    headless.get_engine().set_timers(timers);
+    headless.step();
+
+    // The click arrives long after the last frame, as it would in event driven mode.
+    headless.get_clock().advance(Duration::from_millis(900));
+
+    let clicked_at = headless.get_clock().now();
+
+    headless.mouse_move(30, 30);
+    headless.mouse_button(1, true);
+    headless.mouse_button(1, false);
+    headless.step();
+
+    assert_eq!(fired_count.get(), 0);
+
+    headless
+        .get_clock()
+        .set(clicked_at + Duration::from_millis(450));
+    headless.step();
+
+    assert_eq!(fired_count.get(), 0);
+
+    headless.get_clock().advance(Duration::from_millis(100));
+    headless.step();
+
+    assert_eq!(fired_count.get(), 1);
+}